@@ -1,26 +1,40 @@
 #![feature(duration_constructors)]
 #![feature(exit_status_error)]
 
-use std::{ops::Deref, process::ExitStatus, sync::Arc, time::Duration};
+use std::{
+	collections::HashMap,
+	ops::Deref,
+	process::ExitStatus,
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 use anyhow::anyhow;
 use azalea::{
-	Account, BotClientExt, Client, Event,
-	entity::EntityUuid,
+	Account, BlockPos, BotClientExt, Client, Event, GameProfileComponent, Vec3,
+	core::position::ChunkPos,
+	entity::{EntityUuid, Position},
+	prelude::PathfinderClientExt,
 	protocol::packets::game::ClientboundGamePacket,
 	swarm::{Swarm, SwarmBuilder, SwarmEvent},
 	world::MinecraftEntityId,
 };
 use tasks::{
 	Task,
-	net::{Tasks, start_server},
+	net::{TaskResult, Tasks, report::WorldReports, start_server},
+	survival::{self, Vitals},
+};
+use tokio::{
+	sync::{Mutex, Notify},
+	task::JoinHandle,
 };
-use tokio::{sync::Mutex, task::JoinHandle};
+use uuid::Uuid;
 
 const DEFAULT_OWNER: &str = "manen_";
 const ACCOUNTS: usize = 20;
 
 pub mod namegen;
+pub mod schematic;
 pub mod tasks;
 
 #[tokio::main]
@@ -135,6 +149,7 @@ async fn clients(args: impl IntoIterator<Item = String>) -> anyhow::Result<()> {
 	let mut builder = SwarmBuilder::new()
 		.set_handler(handle)
 		.set_swarm_handler(swarm_handler);
+	let reports = Arc::new(parking_lot::Mutex::new(WorldReports::default()));
 
 	// imma leave it like this
 	// but instead of RequestName we should make Hello mandatory and have it return an inst_id and the username to take
@@ -149,6 +164,12 @@ async fn clients(args: impl IntoIterator<Item = String>) -> anyhow::Result<()> {
 				tasks: Some(Arc::new(Mutex::new(tasks))),
 				handle: Arc::new(Mutex::new(None)),
 				self_eid: Arc::new(Mutex::new(None)),
+				reports: reports.clone(),
+				hurt_by: Arc::new(parking_lot::Mutex::new(None)),
+				hurt_at: Arc::new(parking_lot::Mutex::new(None)),
+				died: Arc::new(Notify::new()),
+				health: Arc::new(parking_lot::Mutex::new(20.0)),
+				entities: Arc::new(parking_lot::Mutex::new(HashMap::new())),
 			},
 		)
 	}
@@ -160,6 +181,12 @@ async fn clients(args: impl IntoIterator<Item = String>) -> anyhow::Result<()> {
 			))),
 			handle: Arc::new(Mutex::new(None)),
 			self_eid: Arc::new(Mutex::new(None)),
+			reports,
+			hurt_by: Arc::new(parking_lot::Mutex::new(None)),
+			hurt_at: Arc::new(parking_lot::Mutex::new(None)),
+			died: Arc::new(Notify::new()),
+			health: Arc::new(parking_lot::Mutex::new(20.0)),
+			entities: Arc::new(parking_lot::Mutex::new(HashMap::new())),
 		})
 		.join_delay(Duration::from_millis(50))
 		.start("localhost")
@@ -180,6 +207,20 @@ pub struct State {
 	tasks: Option<Arc<Mutex<Tasks>>>,
 	handle: Arc<Mutex<Option<JoinHandle<()>>>>,
 	self_eid: Arc<Mutex<Option<MinecraftEntityId>>>,
+	/// shared between every instance in the process
+	reports: Arc<parking_lot::Mutex<WorldReports>>,
+	/// what hurt us last, and its name if it's a player \
+	/// it's reported once the health update says how much it hurt
+	hurt_by: Arc<parking_lot::Mutex<Option<(Uuid, Option<String>)>>>,
+	/// where whatever hurt us last was, and when, so it knows where to run from
+	hurt_at: Arc<parking_lot::Mutex<Option<(Vec3, Instant)>>>,
+	/// interrupts whatever task it was doing when it died
+	died: Arc<Notify>,
+	/// health as of the last health update
+	health: Arc<parking_lot::Mutex<f32>>,
+	/// entity id -> uuid and where it spawned, for every entity the server told us about \
+	/// azalea might've already dropped an entity by the time we handle the packet that removes it
+	entities: Arc<parking_lot::Mutex<HashMap<MinecraftEntityId, (Uuid, Vec3)>>>,
 }
 
 async fn swarm_handler(swarm: Swarm, event: SwarmEvent, state: State) {
@@ -247,12 +288,43 @@ async fn handle(bot: Client, event: Event, state: State) -> anyhow::Result<()> {
 									_ => bot.set_jumping(false),
 								}
 							}
-							match task.execute(&bot).await {
-								Ok(a) => a,
-								Err(err) => {
-									eprintln!("{} couldn't execute {task:?}: {err}", bot.username())
+							let threat = || survival::threat(*state.hurt_at.lock());
+							let result = tokio::select! {
+								result = task.execute(&bot) => match result {
+									Ok(()) => TaskResult::Done,
+									Err(err) => {
+										eprintln!(
+											"{} couldn't execute {task:?}: {err}",
+											bot.username()
+										);
+										if err.downcast_ref::<pathfind::NoTool>().is_some() {
+											TaskResult::NoTool(err.to_string())
+										} else {
+											TaskResult::Failed(err.to_string())
+										}
+									}
+								},
+								// attacks are short and retreat on their own
+								reason = survival::emergency(&bot, threat), if !matches!(task, Task::Attack(_)) => {
+									bot.stop_pathfinding();
+									TaskResult::Interrupted(reason)
+								}
+								_ = state.died.notified() => {
+									bot.stop_pathfinding();
+									TaskResult::Interrupted(format!("{} died", bot.username()))
 								}
 							};
+							{
+								let tasks = match &state.tasks {
+									Some(a) => a,
+									None => return Err(anyhow!("state.tasks is None")),
+								};
+								let mut tasks = tasks.lock().await;
+								tasks.done(result).await?;
+								tasks.report_inventory(&bot).await?;
+								tasks.report_containers().await?;
+							}
+							survival::recover(&bot, threat()).await;
 						}
 						Ok(())
 					};
@@ -265,14 +337,135 @@ async fn handle(bot: Client, event: Event, state: State) -> anyhow::Result<()> {
 				}));
 			}
 		}
+		Event::Death(_) => {
+			let pos = bot.position();
+			println!("{} died at {pos}", bot.username());
+			state.died.notify_waiters();
+
+			if let Some(tasks) = &state.tasks {
+				let mut tasks = tasks.lock().await;
+				tasks.died(pos).await?;
+			}
+			// azalea's AutoRespawnPlugin respawns it
+		}
 		Event::Tick => {
 			// todo state.tasks.tick(&bot).await;
+
+			if !state.reports.lock().is_empty() {
+				if let Some(tasks) = &state.tasks {
+					// the task loop holds the lock while it's waiting for its next task,
+					// we'll just try again next tick
+					if let Ok(mut tasks) = tasks.try_lock() {
+						tasks.report_world(&bot, &state.reports).await?;
+					}
+				}
+			}
 		}
 		Event::Packet(p) => match p.as_ref() {
 			ClientboundGamePacket::Login(login) => {
 				let mut eid = state.self_eid.lock().await;
 				*eid = Some(login.player_id);
 			}
+			ClientboundGamePacket::BlockUpdate(update) => {
+				state.reports.lock().block(update.pos, update.block_state);
+			}
+			ClientboundGamePacket::SectionBlocksUpdate(update) => {
+				let mut reports = state.reports.lock();
+				let section = update.section_pos;
+				for block in update.states.iter() {
+					let pos = BlockPos::new(
+						section.x * 16 + block.pos.x as i32,
+						section.y * 16 + block.pos.y as i32,
+						section.z * 16 + block.pos.z as i32,
+					);
+					reports.block(pos, block.state);
+				}
+			}
+			ClientboundGamePacket::LevelChunkWithLight(chunk) => {
+				let mut reports = state.reports.lock();
+				reports.chunk_loaded(ChunkPos::new(chunk.x, chunk.z));
+			}
+			ClientboundGamePacket::ForgetLevelChunk(forget) => {
+				let mut reports = state.reports.lock();
+				reports.chunk_unloaded(forget.pos);
+			}
+			ClientboundGamePacket::SetHealth(health) => {
+				let damage = {
+					let mut last = state.health.lock();
+					let damage = *last - health.health;
+					*last = health.health;
+					damage
+				};
+				let hurt_by = state.hurt_by.lock().take();
+				if let Some(tasks) = state.tasks {
+					let mut tasks = tasks.lock().await;
+					if let Some((uuid, player)) = hurt_by {
+						if damage > 0.0 {
+							// send the signal for the others to attack
+							tasks.agro(uuid, player, damage).await?;
+						}
+					}
+					let vitals = Vitals {
+						health: health.health,
+						food: health.food,
+					};
+					tasks.report_vitals(vitals).await?;
+				}
+			}
+			ClientboundGamePacket::EntityEvent(event) => {
+				// 3 is a living entity dying
+				if event.event_id == 3 {
+					if let Some((uuid, _)) = entity_uuid(&bot, event.entity_id) {
+						if let Some(tasks) = state.tasks {
+							let mut tasks = tasks.lock().await;
+							tasks.entity_gone(uuid).await?;
+						}
+					}
+				}
+			}
+			ClientboundGamePacket::AddEntity(add) => {
+				state
+					.entities
+					.lock()
+					.insert(add.id, (add.uuid, add.position));
+			}
+			ClientboundGamePacket::RemoveEntities(remove) => {
+				// entities far away are just out of view, close ones despawned
+				let me = bot.position();
+				let gone = {
+					let mut entities = state.entities.lock();
+					remove
+						.entity_ids
+						.iter()
+						.filter_map(|eid| {
+							let (uuid, spawned_at) = entities.remove(eid)?;
+							// where it is now if azalea still has it, where it spawned otherwise
+							let pos = entity_uuid(&bot, *eid)
+								.map(|(_, pos)| pos)
+								.unwrap_or(spawned_at);
+							Some((uuid, pos))
+						})
+						.filter(|(_, pos)| pos.distance_to(&me) < 32.0)
+						.collect::<Vec<_>>()
+				};
+				if !gone.is_empty() {
+					if let Some(tasks) = state.tasks {
+						let mut tasks = tasks.lock().await;
+						for (uuid, _) in gone {
+							tasks.entity_gone(uuid).await?;
+						}
+					}
+				}
+			}
+			ClientboundGamePacket::PlayerInfoRemove(remove) => {
+				// logged out
+				if let Some(tasks) = state.tasks {
+					let mut tasks = tasks.lock().await;
+					for uuid in remove.profile_ids.iter() {
+						tasks.entity_gone(*uuid).await?;
+					}
+				}
+			}
 			ClientboundGamePacket::DamageEvent(dmg) => {
 				let self_eid = {
 					let self_eid = state.self_eid.lock().await;
@@ -296,12 +489,19 @@ async fn handle(bot: Client, event: Event, state: State) -> anyhow::Result<()> {
 							if let Some(damager) = damager {
 								let uuid: Option<EntityUuid> = bot.get_entity_component(damager);
 
+								let pos: Option<Position> = bot.get_entity_component(damager);
+								if let Some(pos) = pos {
+									*state.hurt_at.lock() = Some((pos.down(0.0), Instant::now()));
+								}
+
 								if let Some(uuid) = uuid {
-									// send the signal for the others to attack
-									if let Some(tasks) = state.tasks {
-										let mut tasks = tasks.lock().await;
-										tasks.agro(uuid.deref().clone()).await?;
-									}
+									// the coordinator decides whether it's worth fighting back once we know how much it hurt
+									let player: Option<GameProfileComponent> =
+										bot.get_entity_component(damager);
+									*state.hurt_by.lock() = Some((
+										uuid.deref().clone(),
+										player.map(|profile| profile.name.clone()),
+									));
 								} else {
 									eprintln!(
 										"got damaged and could identify the entity doing the damaging but that entity doesn't have a EntityUuid component"
@@ -318,3 +518,15 @@ async fn handle(bot: Client, event: Event, state: State) -> anyhow::Result<()> {
 	}
 	Ok(())
 }
+
+/// the uuid and position of an entity the bot can see
+fn entity_uuid(bot: &Client, eid: MinecraftEntityId) -> Option<(Uuid, Vec3)> {
+	let entity = {
+		let world = bot.world();
+		let world = world.read();
+		world.entity_by_id.get(&eid).cloned()
+	}?;
+	let uuid: EntityUuid = bot.get_entity_component(entity)?;
+	let pos: Position = bot.get_entity_component(entity)?;
+	Some((*uuid, pos.down(0.0)))
+}
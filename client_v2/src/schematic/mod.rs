@@ -0,0 +1,364 @@
+// loads sponge .schem, litematica .litematic and vanilla structure .nbt files
+
+use std::{collections::HashMap, path::Path};
+
+use anyhow::anyhow;
+
+pub mod nbt;
+use nbt::Tag;
+
+#[derive(Clone, Debug, Default)]
+pub struct Schematic {
+	/// (x, y, z)
+	pub size: [i32; 3],
+	/// offset from the schematic's lowest corner -> block state, like minecraft:oak_stairs[facing=east] \
+	/// air is included
+	pub blocks: Vec<([i32; 3], String)>,
+}
+impl Schematic {
+	pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+		let path = path.as_ref();
+		let bytes = std::fs::read(path)?;
+		let root = nbt::read(&bytes)?;
+
+		let schematic = match path.extension().and_then(|ext| ext.to_str()) {
+			Some("schem") | Some("schematic") => Self::sponge(&root)?,
+			Some("litematic") => Self::litematic(&root)?,
+			Some("nbt") => Self::structure(&root)?,
+			_ => {
+				return Err(anyhow!(
+					"{} isn't a .schem, .litematic or .nbt file",
+					path.display()
+				));
+			}
+		};
+		Ok(schematic.without_void())
+	}
+
+	/// sponge schematic v1 - v3
+	fn sponge(root: &Tag) -> anyhow::Result<Self> {
+		// v3 nests everything one level deeper
+		let root = root.get("Schematic").unwrap_or(root);
+		let blocks = root.get("Blocks").unwrap_or(root);
+
+		let dim = |key| {
+			root.get(key)
+				.and_then(Tag::int)
+				// they're unsigned shorts
+				.map(|a| a & 0xffff)
+				.ok_or_else(|| anyhow!("schematic has no {key}"))
+		};
+		let size = [dim("Width")?, dim("Height")?, dim("Length")?];
+
+		let palette = blocks
+			.get("Palette")
+			.and_then(Tag::compound)
+			.ok_or_else(|| anyhow!("schematic has no palette"))?
+			.iter()
+			.filter_map(|(state, id)| Some((id.int()?, state.clone())))
+			.collect::<HashMap<_, _>>();
+		let data = blocks
+			.get("Data")
+			.or_else(|| blocks.get("BlockData"))
+			.and_then(Tag::byte_array)
+			.ok_or_else(|| anyhow!("schematic has no block data"))?;
+
+		let mut indices = Vec::new();
+		let mut data = data.iter().map(|a| *a as u8);
+		while let Some(index) = varint(&mut data) {
+			indices.push(index);
+		}
+
+		let blocks = indices
+			.into_iter()
+			.enumerate()
+			.filter_map(|(i, index)| {
+				let i = i as i32;
+				let x = i % size[0];
+				let z = (i / size[0]) % size[2];
+				let y = i / (size[0] * size[2]);
+				Some(([x, y, z], palette.get(&index)?.clone()))
+			})
+			.collect();
+
+		Ok(Self { size, blocks })
+	}
+
+	/// every region of a litematica schematic, relative to the lowest corner of all of them
+	fn litematic(root: &Tag) -> anyhow::Result<Self> {
+		let regions = root
+			.get("Regions")
+			.and_then(Tag::compound)
+			.ok_or_else(|| anyhow!("litematic has no regions"))?;
+
+		let vec3 = |tag: Option<&Tag>| -> anyhow::Result<[i32; 3]> {
+			let tag =
+				tag.ok_or_else(|| anyhow!("litematic region is missing a position or size"))?;
+			let get = |key| tag.get(key).and_then(Tag::int).unwrap_or_default();
+			Ok([get("x"), get("y"), get("z")])
+		};
+
+		let mut blocks = Vec::new();
+		for region in regions.values() {
+			let pos = vec3(region.get("Position"))?;
+			let size = vec3(region.get("Size"))?;
+			// negative sizes mean the region extends backwards from its position
+			let origin = [0, 1, 2].map(|i| pos[i] + if size[i] < 0 { size[i] + 1 } else { 0 });
+			let size = size.map(|a| a.abs());
+
+			let palette = region
+				.get("BlockStatePalette")
+				.and_then(Tag::list)
+				.ok_or_else(|| anyhow!("litematic region has no palette"))?
+				.iter()
+				.map(block_state)
+				.collect::<Vec<_>>();
+			let states = region
+				.get("BlockStates")
+				.and_then(Tag::long_array)
+				.ok_or_else(|| anyhow!("litematic region has no block states"))?;
+
+			// entries are packed tightly and can span two longs
+			let bits = (usize::BITS - (palette.len().max(1) - 1).leading_zeros()).max(2) as usize;
+			let mask = (1_u64 << bits) - 1;
+			let entry = |i: usize| -> Option<usize> {
+				let bit = i * bits;
+				let (long, offset) = (bit / 64, bit % 64);
+				let mut value = *states.get(long)? as u64 >> offset;
+				if offset + bits > 64 {
+					value |= (*states.get(long + 1)? as u64) << (64 - offset);
+				}
+				Some((value & mask) as usize)
+			};
+
+			let volume = (size[0] * size[1] * size[2]) as usize;
+			for i in 0..volume {
+				let Some(state) = entry(i).and_then(|index| palette.get(index)) else {
+					continue;
+				};
+				let i = i as i32;
+				let x = i % size[0];
+				let z = (i / size[0]) % size[2];
+				let y = i / (size[0] * size[2]);
+				blocks.push(([origin[0] + x, origin[1] + y, origin[2] + z], state.clone()));
+			}
+		}
+
+		Ok(Self::from_absolute(blocks))
+	}
+
+	/// vanilla structure block files
+	fn structure(root: &Tag) -> anyhow::Result<Self> {
+		let palette = root
+			.get("palette")
+			.or_else(|| root.get("palettes").and_then(Tag::list)?.first())
+			.and_then(Tag::list)
+			.ok_or_else(|| anyhow!("structure has no palette"))?
+			.iter()
+			.map(block_state)
+			.collect::<Vec<_>>();
+
+		let blocks = root
+			.get("blocks")
+			.and_then(Tag::list)
+			.ok_or_else(|| anyhow!("structure has no blocks"))?
+			.iter()
+			.filter_map(|block| {
+				let pos = block.get("pos").and_then(Tag::list)?;
+				let pos = [pos.first()?.int()?, pos.get(1)?.int()?, pos.get(2)?.int()?];
+				let state = block.get("state").and_then(Tag::int)?;
+				Some((pos, palette.get(state as usize)?.clone()))
+			})
+			.collect();
+
+		Ok(Self::from_absolute(blocks))
+	}
+
+	/// moves the blocks so the lowest corner is at 0, 0, 0
+	fn from_absolute(blocks: Vec<([i32; 3], String)>) -> Self {
+		let mut min = [i32::MAX; 3];
+		let mut max = [i32::MIN; 3];
+		for (pos, _) in blocks.iter() {
+			min = [0, 1, 2].map(|i| min[i].min(pos[i]));
+			max = [0, 1, 2].map(|i| max[i].max(pos[i]));
+		}
+		if blocks.is_empty() {
+			return Self::default();
+		}
+
+		Self {
+			size: [0, 1, 2].map(|i| max[i] - min[i] + 1),
+			blocks: blocks
+				.into_iter()
+				.map(|(pos, block)| ([0, 1, 2].map(|i| pos[i] - min[i]), block))
+				.collect(),
+		}
+	}
+
+	/// structure void means "leave whatever's there alone"
+	fn without_void(mut self) -> Self {
+		self.blocks
+			.retain(|(_, state)| block_id(state) != "minecraft:structure_void");
+		self
+	}
+
+	/// turned clockwise (seen from above) `turns` times, with the lowest corner still at 0, 0, 0
+	pub fn rotated(&self, turns: u8) -> Self {
+		let mut rotated = self.clone();
+		for _ in 0..turns % 4 {
+			let [sx, sy, sz] = rotated.size;
+			rotated.size = [sz, sy, sx];
+			for ([x, _, z], state) in rotated.blocks.iter_mut() {
+				// x is east and z is south, so east turns into south
+				(*x, *z) = (sz - 1 - *z, *x);
+				*state = rotate_state(state);
+			}
+		}
+		rotated
+	}
+}
+
+/// a palette entry of a litematic or a structure file, as minecraft:oak_stairs[facing=east]
+fn block_state(tag: &Tag) -> String {
+	let name = tag
+		.get("Name")
+		.and_then(Tag::str)
+		.unwrap_or("minecraft:air");
+	let properties = tag
+		.get("Properties")
+		.and_then(Tag::compound)
+		.map(|properties| {
+			properties
+				.iter()
+				.filter_map(|(key, value)| Some(format!("{key}={}", value.str()?)))
+				.collect::<Vec<_>>()
+		})
+		.unwrap_or_default();
+
+	if properties.is_empty() {
+		name.to_owned()
+	} else {
+		format!("{name}[{}]", properties.join(","))
+	}
+}
+
+/// the block state turned clockwise once: facing, axis, rotation, and the sides of fences and such
+fn rotate_state(state: &str) -> String {
+	let Some((id, properties)) = state.strip_suffix(']').and_then(|a| a.split_once('[')) else {
+		return state.to_owned();
+	};
+	fn turn(dir: &str) -> &str {
+		match dir {
+			"north" => "east",
+			"east" => "south",
+			"south" => "west",
+			"west" => "north",
+			other => other,
+		}
+	}
+
+	let properties = properties
+		.split(',')
+		.map(|property| {
+			let Some((key, value)) = property.split_once('=') else {
+				return property.to_owned();
+			};
+			let value = match key {
+				"facing" => turn(value).to_owned(),
+				"axis" => match value {
+					"x" => "z",
+					"z" => "x",
+					other => other,
+				}
+				.to_owned(),
+				"rotation" => match value.parse::<u8>() {
+					Ok(rotation) => ((rotation + 4) % 16).to_string(),
+					Err(_) => value.to_owned(),
+				},
+				// rails: north_south, ascending_east, south_west...
+				"shape"
+					if value.contains('_')
+						&& !value.contains("inner")
+						&& !value.contains("outer") =>
+				{
+					match value {
+						"north_south" => "east_west".to_owned(),
+						"east_west" => "north_south".to_owned(),
+						_ => match value.strip_prefix("ascending_") {
+							Some(dir) => format!("ascending_{}", turn(dir)),
+							None => {
+								let mut parts = value.split('_').map(turn).collect::<Vec<_>>();
+								// curves are always named north/south first
+								if matches!(parts[..], ["east" | "west", "north" | "south"]) {
+									parts.swap(0, 1);
+								}
+								parts.join("_")
+							}
+						},
+					}
+				}
+				_ => value.to_owned(),
+			};
+			format!("{}={value}", turn(key))
+		})
+		.collect::<Vec<_>>();
+	format!("{id}[{}]", properties.join(","))
+}
+
+/// minecraft:oak_stairs[facing=east] -> minecraft:oak_stairs
+pub fn block_id(state: &str) -> &str {
+	state.split('[').next().unwrap_or(state)
+}
+
+fn varint(bytes: &mut impl Iterator<Item = u8>) -> Option<i32> {
+	let mut value = 0_i32;
+	for i in 0..5 {
+		let byte = bytes.next()?;
+		value |= ((byte & 0x7f) as i32) << (i * 7);
+		if byte & 0x80 == 0 {
+			break;
+		}
+	}
+	Some(value)
+}
+
+#[test]
+fn rotates_clockwise() {
+	let schematic = Schematic {
+		size: [2, 1, 1],
+		blocks: vec![
+			([0, 0, 0], "minecraft:stone".to_owned()),
+			(
+				[1, 0, 0],
+				"minecraft:oak_stairs[facing=north,half=bottom]".to_owned(),
+			),
+		],
+	};
+	let rotated = schematic.rotated(1);
+	assert_eq!(rotated.size, [1, 1, 2]);
+	assert_eq!(
+		rotated.blocks,
+		vec![
+			([0, 0, 0], "minecraft:stone".to_owned()),
+			(
+				[0, 0, 1],
+				"minecraft:oak_stairs[facing=east,half=bottom]".to_owned()
+			),
+		]
+	);
+	for (shape, turned) in [
+		("east_west", "north_south"),
+		("north_south", "east_west"),
+		("ascending_north", "ascending_east"),
+		("ascending_west", "ascending_north"),
+		("north_east", "south_east"),
+		("south_east", "south_west"),
+		("south_west", "north_west"),
+		("north_west", "north_east"),
+	] {
+		assert_eq!(
+			rotate_state(&format!("minecraft:rail[shape={shape}]")),
+			format!("minecraft:rail[shape={turned}]")
+		);
+	}
+}
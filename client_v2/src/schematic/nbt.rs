@@ -0,0 +1,197 @@
+// just enough of an nbt reader to load schematics with
+
+use std::{collections::HashMap, io::Read};
+
+use anyhow::anyhow;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tag {
+	Byte(i8),
+	Short(i16),
+	Int(i32),
+	Long(i64),
+	Float(f32),
+	Double(f64),
+	ByteArray(Vec<i8>),
+	String(String),
+	List(Vec<Tag>),
+	Compound(HashMap<String, Tag>),
+	IntArray(Vec<i32>),
+	LongArray(Vec<i64>),
+}
+impl Tag {
+	pub fn get(&self, key: &str) -> Option<&Tag> {
+		match self {
+			Self::Compound(map) => map.get(key),
+			_ => None,
+		}
+	}
+	pub fn int(&self) -> Option<i32> {
+		match self {
+			Self::Byte(a) => Some(*a as i32),
+			Self::Short(a) => Some(*a as i32),
+			Self::Int(a) => Some(*a),
+			_ => None,
+		}
+	}
+	pub fn str(&self) -> Option<&str> {
+		match self {
+			Self::String(a) => Some(a),
+			_ => None,
+		}
+	}
+	pub fn list(&self) -> Option<&[Tag]> {
+		match self {
+			Self::List(a) => Some(a),
+			_ => None,
+		}
+	}
+	pub fn compound(&self) -> Option<&HashMap<String, Tag>> {
+		match self {
+			Self::Compound(a) => Some(a),
+			_ => None,
+		}
+	}
+	pub fn byte_array(&self) -> Option<&[i8]> {
+		match self {
+			Self::ByteArray(a) => Some(a),
+			_ => None,
+		}
+	}
+	pub fn long_array(&self) -> Option<&[i64]> {
+		match self {
+			Self::LongArray(a) => Some(a),
+			_ => None,
+		}
+	}
+}
+
+/// reads an nbt file, gzipped or not, returning the root compound
+pub fn read(bytes: &[u8]) -> anyhow::Result<Tag> {
+	let bytes = if bytes.starts_with(&[0x1f, 0x8b]) {
+		let mut decoded = Vec::new();
+		flate2::read::GzDecoder::new(bytes).read_to_end(&mut decoded)?;
+		decoded
+	} else {
+		bytes.to_vec()
+	};
+
+	let mut reader = Reader {
+		bytes: &bytes,
+		i: 0,
+	};
+	match reader.u8()? {
+		10 => {
+			// the root's name
+			reader.string()?;
+			reader.tag(10)
+		}
+		id => Err(anyhow!("expected the root tag to be a compound, it's {id}")),
+	}
+}
+
+struct Reader<'a> {
+	bytes: &'a [u8],
+	i: usize,
+}
+impl<'a> Reader<'a> {
+	fn take(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+		let taken = self
+			.bytes
+			.get(self.i..self.i + n)
+			.ok_or_else(|| anyhow!("unexpected end of nbt data"))?;
+		self.i += n;
+		Ok(taken)
+	}
+	fn u8(&mut self) -> anyhow::Result<u8> {
+		Ok(self.take(1)?[0])
+	}
+	fn i16(&mut self) -> anyhow::Result<i16> {
+		Ok(i16::from_be_bytes(self.take(2)?.try_into()?))
+	}
+	fn i32(&mut self) -> anyhow::Result<i32> {
+		Ok(i32::from_be_bytes(self.take(4)?.try_into()?))
+	}
+	fn i64(&mut self) -> anyhow::Result<i64> {
+		Ok(i64::from_be_bytes(self.take(8)?.try_into()?))
+	}
+	fn len(&mut self) -> anyhow::Result<usize> {
+		Ok(self.i32()?.max(0) as usize)
+	}
+	fn string(&mut self) -> anyhow::Result<String> {
+		let len = self.i16()? as u16 as usize;
+		// it's actually modified utf-8 but that only matters for characters nobody puts in block names
+		Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+	}
+
+	fn tag(&mut self, id: u8) -> anyhow::Result<Tag> {
+		Ok(match id {
+			1 => Tag::Byte(self.u8()? as i8),
+			2 => Tag::Short(self.i16()?),
+			3 => Tag::Int(self.i32()?),
+			4 => Tag::Long(self.i64()?),
+			5 => Tag::Float(f32::from_bits(self.i32()? as u32)),
+			6 => Tag::Double(f64::from_bits(self.i64()? as u64)),
+			7 => {
+				let len = self.len()?;
+				Tag::ByteArray(self.take(len)?.iter().map(|a| *a as i8).collect())
+			}
+			8 => Tag::String(self.string()?),
+			9 => {
+				let id = self.u8()?;
+				let len = self.len()?;
+				Tag::List(
+					(0..len)
+						.map(|_| self.tag(id))
+						.collect::<anyhow::Result<_>>()?,
+				)
+			}
+			10 => {
+				let mut map = HashMap::new();
+				loop {
+					let id = self.u8()?;
+					if id == 0 {
+						break;
+					}
+					let name = self.string()?;
+					map.insert(name, self.tag(id)?);
+				}
+				Tag::Compound(map)
+			}
+			11 => {
+				let len = self.len()?;
+				Tag::IntArray(
+					(0..len)
+						.map(|_| self.i32())
+						.collect::<anyhow::Result<_>>()?,
+				)
+			}
+			12 => {
+				let len = self.len()?;
+				Tag::LongArray(
+					(0..len)
+						.map(|_| self.i64())
+						.collect::<anyhow::Result<_>>()?,
+				)
+			}
+			id => return Err(anyhow!("unknown nbt tag id {id}")),
+		})
+	}
+}
+
+#[test]
+fn reads_compound() {
+	// {"": {size: [I; 1, 2], name: "stone"}} but with size being a list of ints
+	let bytes = [
+		10, 0, 0, // root compound with an empty name
+		9, 0, 4, b's', b'i', b'z', b'e', 3, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0,
+		2, // size: [1, 2]
+		8, 0, 4, b'n', b'a', b'm', b'e', 0, 5, b's', b't', b'o', b'n', b'e', // name: "stone"
+		0,
+	];
+	let root = read(&bytes).unwrap();
+
+	let size = root.get("size").and_then(Tag::list).unwrap();
+	assert_eq!(size, &[Tag::Int(1), Tag::Int(2)]);
+	assert_eq!(root.get("name").and_then(Tag::str), Some("stone"));
+}
@@ -0,0 +1,219 @@
+use std::{collections::HashMap, ops::Deref, sync::LazyLock, time::Duration};
+
+use anyhow::anyhow;
+use azalea::{
+	BotClientExt, Client, WalkDirection,
+	entity::{EyeHeight, Physics, Position},
+	pathfinder::goals::{Goal, RadiusGoal},
+	prelude::PathfinderClientExt,
+	registry::Item,
+	world::MinecraftEntityId,
+};
+use uuid::Uuid;
+
+/// how each bot fights, set with `gang combat`
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CombatStyle {
+	/// runs away and eats once its health gets this low
+	pub retreat_health: f32,
+	/// jumps before every hit so it lands as a critical hit
+	pub crits: bool,
+	/// walks sideways while waiting for the attack cooldown
+	pub strafe: bool,
+}
+impl Default for CombatStyle {
+	fn default() -> Self {
+		Self {
+			retreat_health: 6.0,
+			crits: true,
+			strafe: true,
+		}
+	}
+}
+impl CombatStyle {
+	pub fn set(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+		match key {
+			"retreat" => self.retreat_health = value.parse()?,
+			"crits" => self.crits = parse_bool(value)?,
+			"strafe" => self.strafe = parse_bool(value)?,
+			_ => return Err(anyhow!("there's no combat setting called {key}")),
+		}
+		Ok(())
+	}
+}
+
+fn parse_bool(value: &str) -> anyhow::Result<bool> {
+	match value {
+		"on" | "true" | "yes" => Ok(true),
+		"off" | "false" | "no" => Ok(false),
+		_ => Err(anyhow!("expected on or off, got {value}")),
+	}
+}
+
+/// how every bot in the process fights
+#[derive(Debug, Default)]
+struct Styles {
+	default: CombatStyle,
+	/// username -> style, for the bots that fight differently
+	per_bot: HashMap<String, CombatStyle>,
+}
+static STYLES: LazyLock<parking_lot::Mutex<Styles>> = LazyLock::new(Default::default);
+
+/// sets one bot's style, or everyone's if there's no username
+pub fn set_style(username: Option<String>, style: CombatStyle) {
+	let mut styles = STYLES.lock();
+	match username {
+		Some(username) => {
+			styles.per_bot.insert(username, style);
+		}
+		None => {
+			styles.per_bot.clear();
+			styles.default = style;
+		}
+	}
+}
+pub fn style(username: &str) -> CombatStyle {
+	let styles = STYLES.lock();
+	styles
+		.per_bot
+		.get(username)
+		.cloned()
+		.unwrap_or_else(|| styles.default.clone())
+}
+
+/// melee weapons, best first
+const WEAPONS: &[Item] = &[
+	Item::NetheriteSword,
+	Item::DiamondSword,
+	Item::NetheriteAxe,
+	Item::IronSword,
+	Item::DiamondAxe,
+	Item::StoneSword,
+	Item::IronAxe,
+	Item::GoldenSword,
+	Item::WoodenSword,
+	Item::StoneAxe,
+	Item::GoldenAxe,
+	Item::WoodenAxe,
+];
+
+/// goes after the entity and hits it once, properly timed
+pub async fn attack(bot: &Client, uuid: Uuid) -> anyhow::Result<()> {
+	let style = style(&bot.username());
+
+	let entity = bot
+		.entity_by_uuid(uuid)
+		.ok_or_else(|| anyhow!("couldn't find an entity with uuid {uuid}"))?;
+
+	let eid: MinecraftEntityId = bot.get_entity_component(entity).ok_or_else(|| {
+		anyhow!(
+			"there wasn't an entityid component on the entity {} was supposed to attack",
+			bot.username()
+		)
+	})?;
+
+	let eye_offset: Option<EyeHeight> = bot.get_entity_component(entity);
+	let eye_offset = eye_offset.map(|a| a.deref().clone()).unwrap_or_default();
+
+	let pos_now = || {
+		let pos: Position = bot.get_entity_component(entity).ok_or_else(|| {
+			anyhow!(
+				"there wasn't a position component on the entity {} was supposed to attack",
+				bot.username()
+			)
+		})?;
+		let pos = pos.down(0.0);
+		anyhow::Ok(pos)
+	};
+
+	if bot.health() <= style.retreat_health {
+		super::survival::flee(bot, pos_now()?).await;
+		super::survival::eat(bot).await;
+		return Err(anyhow!("{} retreated at low health", bot.username()));
+	}
+
+	for weapon in WEAPONS {
+		let held = pathfind::hold_slot_matching(bot, |stack| {
+			stack.kind() == *weapon && !pathfind::about_to_break(stack)
+		})
+		.await;
+		if held {
+			break;
+		}
+	}
+
+	loop {
+		let start_pos = pos_now()?;
+		let goal = RadiusGoal {
+			pos: start_pos,
+			radius: 3.0,
+		};
+		if goal.success(bot.position().to_block_pos_floor()) {
+			break;
+		} else {
+			// this is a reimplementation of bot.goto that will change the target if it moved too far
+			bot.start_goto(goal);
+			bot.wait_one_update().await;
+
+			let mut tick_broadcaster = bot.get_tick_broadcaster();
+			'pathing: while !bot.is_goto_target_reached() {
+				// check every tick
+				match tick_broadcaster.recv().await {
+					Ok(_) => (),
+					Err(_err) => (),
+				};
+				let pos = pos_now()?;
+				if pos.distance_to(&start_pos) >= 5.0 {
+					bot.stop_pathfinding();
+					println!(
+						"looping again in Attack, since the target entity has moved at least 5 blocks"
+					);
+					tokio::time::sleep(Duration::from_millis(100)).await;
+					break 'pathing;
+				}
+			}
+		}
+	}
+
+	// hitting before the cooldown's over barely does any damage
+	if style.strafe {
+		let dir = if rand_bool() {
+			WalkDirection::Left
+		} else {
+			WalkDirection::Right
+		};
+		bot.walk(dir);
+	}
+	for _ in 0..20 {
+		if !bot.has_attack_cooldown() {
+			break;
+		}
+		bot.look_at(pos_now()?.up(eye_offset as _));
+		pathfind::wait_ticks(bot, 1).await;
+	}
+	bot.walk(WalkDirection::None);
+
+	// crits only happen while falling
+	if style.crits && bot.component::<Physics>().on_ground() {
+		bot.jump();
+		for _ in 0..10 {
+			pathfind::wait_ticks(bot, 1).await;
+			if bot.component::<Physics>().velocity.y < 0.0 {
+				break;
+			}
+		}
+	}
+
+	bot.look_at(pos_now()?.up(eye_offset as _));
+	bot.attack(eid);
+	pathfind::wait_ticks(bot, 1).await;
+	Ok(())
+}
+
+/// which way to strafe, it doesn't need to be good randomness
+fn rand_bool() -> bool {
+	std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map(|time| time.subsec_nanos() % 2 == 0)
+		.unwrap_or_default()
+}
@@ -0,0 +1,333 @@
+use std::{collections::HashMap, future::Future, pin::Pin, str::FromStr, sync::LazyLock};
+
+use anyhow::anyhow;
+use azalea::{
+	BotClientExt, Client,
+	blocks::BlockStates,
+	inventory::{
+		Menu,
+		operations::{PickupClick, QuickMoveClick},
+	},
+	prelude::ContainerClientExt,
+	registry::{Block, Item},
+};
+
+/// how many crafting steps deep it goes for missing ingredients, logs -> planks -> sticks is 2
+const MAX_DEPTH: usize = 2;
+
+const WOODS: &[&str] = &[
+	"oak", "spruce", "birch", "jungle", "acacia", "dark_oak", "mangrove", "cherry", "pale_oak",
+	"crimson", "warped",
+];
+
+fn items(ids: impl IntoIterator<Item = String>) -> Vec<Item> {
+	ids.into_iter()
+		.filter_map(|id| Item::from_str(&format!("minecraft:{id}")).ok())
+		.collect()
+}
+
+static PLANKS: LazyLock<Vec<Item>> =
+	LazyLock::new(|| items(WOODS.iter().map(|wood| format!("{wood}_planks"))));
+
+/// a shaped recipe, shapeless ones are written as shaped ones that fit
+#[derive(Clone, Debug)]
+pub struct Recipe {
+	pub result: Item,
+	pub count: i32,
+	/// rows of the grid, every cell being the items that fit in it
+	pub pattern: Vec<Vec<Option<Vec<Item>>>>,
+}
+impl Recipe {
+	/// `rows` are like the recipe files, with a char per cell and spaces for empty ones
+	fn shaped(result: Item, count: i32, rows: &[&str], keys: &[(char, Vec<Item>)]) -> Self {
+		let pattern = rows
+			.iter()
+			.map(|row| {
+				row.chars()
+					.map(|c| {
+						keys.iter()
+							.find(|(key, _)| *key == c)
+							.map(|(_, items)| items.clone())
+					})
+					.collect()
+			})
+			.collect();
+		Self {
+			result,
+			count,
+			pattern,
+		}
+	}
+
+	/// fits in the inventory's 2x2 grid, no crafting table needed
+	pub fn is_small(&self) -> bool {
+		self.pattern.len() <= 2 && self.pattern.iter().all(|row| row.len() <= 2)
+	}
+
+	/// every ingredient and how many of it one craft takes
+	pub fn ingredients(&self) -> Vec<(&[Item], i32)> {
+		let mut ingredients = Vec::<(&[Item], i32)>::new();
+		for cell in self.pattern.iter().flatten().flatten() {
+			match ingredients
+				.iter_mut()
+				.find(|(items, _)| *items == cell.as_slice())
+			{
+				Some((_, count)) => *count += 1,
+				None => ingredients.push((cell, 1)),
+			}
+		}
+		ingredients
+	}
+}
+
+pub static RECIPES: LazyLock<Vec<Recipe>> = LazyLock::new(|| {
+	let mut recipes = Vec::new();
+
+	for wood in WOODS {
+		let (log, wood_block) = match *wood {
+			"crimson" | "warped" => ("stem", "hyphae"),
+			_ => ("log", "wood"),
+		};
+		let logs = items([
+			format!("{wood}_{log}"),
+			format!("stripped_{wood}_{log}"),
+			format!("{wood}_{wood_block}"),
+			format!("stripped_{wood}_{wood_block}"),
+		]);
+		if let [planks] = items([format!("{wood}_planks")]).as_slice() {
+			recipes.push(Recipe::shaped(*planks, 4, &["L"], &[('L', logs)]));
+		}
+	}
+
+	let planks = PLANKS.clone();
+	let stick = vec![Item::Stick];
+	let stone = vec![Item::Cobblestone, Item::CobbledDeepslate, Item::Blackstone];
+	recipes.push(Recipe::shaped(
+		Item::Stick,
+		4,
+		&["P", "P"],
+		&[('P', planks.clone())],
+	));
+	recipes.push(Recipe::shaped(
+		Item::CraftingTable,
+		1,
+		&["PP", "PP"],
+		&[('P', planks.clone())],
+	));
+	recipes.push(Recipe::shaped(
+		Item::Torch,
+		4,
+		&["C", "S"],
+		&[
+			('C', vec![Item::Coal, Item::Charcoal]),
+			('S', stick.clone()),
+		],
+	));
+	recipes.push(Recipe::shaped(
+		Item::Chest,
+		1,
+		&["PPP", "P P", "PPP"],
+		&[('P', planks.clone())],
+	));
+	recipes.push(Recipe::shaped(
+		Item::Furnace,
+		1,
+		&["CCC", "C C", "CCC"],
+		&[('C', stone.clone())],
+	));
+
+	let tools: [(&str, &[&str]); 5] = [
+		("pickaxe", &["XXX", " S ", " S "]),
+		("axe", &["XX", "XS", " S"]),
+		("shovel", &["X", "S", "S"]),
+		("sword", &["X", "X", "S"]),
+		("hoe", &["XX", " S", " S"]),
+	];
+	let materials = [
+		("wooden", planks),
+		("stone", stone),
+		("iron", vec![Item::IronIngot]),
+		("golden", vec![Item::GoldIngot]),
+		("diamond", vec![Item::Diamond]),
+	];
+	for (material, material_items) in materials.iter() {
+		for (tool, rows) in tools.iter() {
+			if let [result] = items([format!("{material}_{tool}")]).as_slice() {
+				recipes.push(Recipe::shaped(
+					*result,
+					1,
+					rows,
+					&[('X', material_items.clone()), ('S', stick.clone())],
+				));
+			}
+		}
+	}
+
+	recipes
+});
+
+pub fn recipe_for(item: Item) -> Option<&'static Recipe> {
+	RECIPES.iter().find(|recipe| recipe.result == item)
+}
+
+/// whether the items are enough for at least one craft of the item, crafting ingredients first if it has to
+pub fn craftable(item: Item, items: &HashMap<Item, i32>) -> bool {
+	craftable_at(item, items, 0)
+}
+fn craftable_at(item: Item, items: &HashMap<Item, i32>, depth: usize) -> bool {
+	let Some(recipe) = recipe_for(item) else {
+		return false;
+	};
+	recipe
+		.ingredients()
+		.into_iter()
+		.all(|(alternatives, per_craft)| {
+			let have: i32 = alternatives
+				.iter()
+				.map(|item| items.get(item).copied().unwrap_or_default())
+				.sum();
+			have >= per_craft
+				|| (depth < MAX_DEPTH
+					&& alternatives
+						.iter()
+						.any(|item| craftable_at(*item, items, depth + 1)))
+		})
+}
+
+/// crafts at least `count` of the item, crafting missing ingredients first \
+/// uses the inventory grid when the recipe fits and the closest crafting table otherwise
+pub async fn craft(bot: &Client, item: Item, count: i32) -> anyhow::Result<()> {
+	craft_at(bot, item, count, 0).await
+}
+
+fn craft_at(
+	bot: &Client,
+	item: Item,
+	count: i32,
+	depth: usize,
+) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + '_>> {
+	Box::pin(async move {
+		let recipe = recipe_for(item).ok_or_else(|| anyhow!("there's no recipe for {item:?}"))?;
+		let crafts = (count + recipe.count - 1) / recipe.count;
+
+		if depth < MAX_DEPTH {
+			for (alternatives, per_craft) in recipe.ingredients() {
+				let need = per_craft * crafts;
+				let have: i32 = alternatives
+					.iter()
+					.map(|item| pathfind::count_item(bot, *item))
+					.sum();
+				if have >= need {
+					continue;
+				}
+				for alternative in alternatives {
+					if recipe_for(*alternative).is_some()
+						&& craft_at(bot, *alternative, need - have, depth + 1)
+							.await
+							.is_ok()
+					{
+						break;
+					}
+				}
+			}
+		}
+
+		let (container, width) = if recipe.is_small() {
+			let inventory = bot.open_inventory().ok_or_else(|| {
+				anyhow!("{} has a container open, it can't craft", bot.username())
+			})?;
+			(inventory, 2)
+		} else {
+			let table = {
+				let world = bot.world();
+				let world = world.read();
+				world.find_block(bot.position(), &BlockStates::from(Block::CraftingTable))
+			}
+			.ok_or_else(|| anyhow!("{} can't see a crafting table", bot.username()))?;
+			pathfind::reach(bot, table).await?;
+			let container = bot
+				.open_container_at(table)
+				.await
+				.ok_or_else(|| anyhow!("{} couldn't open the crafting table", bot.username()))?;
+			(container, 3)
+		};
+
+		let mut crafted = 0;
+		'crafting: for _ in 0..crafts {
+			for (y, row) in recipe.pattern.iter().enumerate() {
+				for (x, cell) in row.iter().enumerate() {
+					let Some(alternatives) = cell else {
+						continue;
+					};
+					let menu = container
+						.menu()
+						.ok_or_else(|| anyhow!("{}'s crafting grid closed", bot.username()))?;
+					let Some((from, _)) = super::storage::player_stacks(&menu)
+						.into_iter()
+						.find(|(_, stack)| alternatives.contains(&stack.kind()))
+					else {
+						break 'crafting;
+					};
+					// slot 0 is the result, the grid comes right after
+					let to = 1 + y * width + x;
+
+					// pick up the stack, drop one in the grid and put the rest back
+					container.click(PickupClick::Left {
+						slot: Some(from as u16),
+					});
+					container.click(PickupClick::Right {
+						slot: Some(to as u16),
+					});
+					container.click(PickupClick::Left {
+						slot: Some(from as u16),
+					});
+				}
+			}
+			// the server decides what the grid makes
+			bot.wait_one_update().await;
+			pathfind::wait_ticks(bot, 2).await;
+
+			let menu = container
+				.menu()
+				.ok_or_else(|| anyhow!("{}'s crafting grid closed", bot.username()))?;
+			if menu.slot(0).is_none_or(|result| result.kind() != item) {
+				break 'crafting;
+			}
+
+			let before = held(&menu, item);
+			container.click(QuickMoveClick::Left { slot: 0 });
+			bot.wait_one_update().await;
+			let menu = container
+				.menu()
+				.ok_or_else(|| anyhow!("{}'s crafting grid closed", bot.username()))?;
+			if held(&menu, item) <= before {
+				// no room for it, or the server didn't agree
+				break 'crafting;
+			}
+			crafted += 1;
+		}
+
+		// whatever's left in the grid from a craft that didn't happen goes back in the inventory
+		for slot in 1..=width * width {
+			container.click(QuickMoveClick::Left { slot: slot as u16 });
+		}
+		bot.wait_one_update().await;
+
+		if crafted == 0 {
+			return Err(anyhow!(
+				"{} doesn't have what it takes to craft {item:?}",
+				bot.username()
+			));
+		}
+		Ok(())
+	})
+}
+
+/// how many of the item are in the player's part of the menu, the result slot doesn't count
+fn held(menu: &Menu, item: Item) -> i32 {
+	super::storage::player_stacks(menu)
+		.into_iter()
+		.filter(|(_, stack)| stack.kind() == item)
+		.map(|(_, stack)| stack.count())
+		.sum()
+}
@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+use azalea::{
+	BotClientExt, Client, Vec3,
+	entity::metadata::ItemItem,
+	inventory::operations::ThrowClick,
+	pathfinder::goals::RadiusGoal,
+	prelude::{ContainerClientExt, PathfinderClientExt},
+	registry::Item,
+};
+use bevy_ecs::entity::Entity;
+
+/// walks up to the player, tosses them the items and waits for them to pick up those exact stacks
+pub async fn deliver(bot: &Client, to: &str, item: Item, count: i32) -> anyhow::Result<()> {
+	if pathfind::count_item(bot, item) == 0 {
+		return Err(anyhow!(
+			"{} doesn't have any {item:?} for {to}",
+			bot.username()
+		));
+	}
+
+	// they might be walking around
+	let mut target = None;
+	for _ in 0..5 {
+		let pos = player_pos(bot, to)
+			.ok_or_else(|| anyhow!("{} can't see {to} to give them {item:?}", bot.username()))?;
+		if pos.distance_to(&bot.position()) <= 3.0 {
+			target = Some(pos);
+			break;
+		}
+		bot.goto(RadiusGoal { pos, radius: 2.0 }).await;
+	}
+	let target = target.ok_or_else(|| anyhow!("{} couldn't catch up with {to}", bot.username()))?;
+
+	bot.look_at(target.up(1.5));
+	pathfind::wait_ticks(bot, 1).await;
+	let lying_before = items_of(bot, item);
+	toss(bot, item, count)?;
+
+	// the stacks it threw are the ones that weren't lying around before
+	pathfind::wait_ticks(bot, 5).await;
+	let held = pathfind::count_item(bot, item);
+	let thrown = items_of(bot, item)
+		.difference(&lying_before)
+		.copied()
+		.collect::<HashSet<_>>();
+	if thrown.is_empty() {
+		return Err(anyhow!(
+			"{} didn't see the {item:?} it threw {to} land",
+			bot.username()
+		));
+	}
+
+	// thrown items can't be picked up for 2 seconds
+	pathfind::wait_ticks(bot, 40).await;
+	let mut picked_up = false;
+	for _ in 0..60 {
+		if items_of(bot, item).is_disjoint(&thrown) {
+			picked_up = true;
+			break;
+		}
+		pathfind::wait_ticks(bot, 1).await;
+	}
+	if !picked_up {
+		return Err(anyhow!(
+			"{to} didn't pick up the {item:?} {} threw them",
+			bot.username()
+		));
+	}
+
+	// it was standing right there, so it might've been the one that picked them up
+	let came_back = pathfind::count_item(bot, item) - held;
+	if came_back > 0 {
+		return Err(anyhow!(
+			"{} picked {came_back} of the {item:?} it threw {to} back up",
+			bot.username()
+		));
+	}
+	Ok(())
+}
+
+/// the item entities of this kind lying around the bot
+fn items_of(bot: &Client, item: Item) -> HashSet<Entity> {
+	crate::tasks::survival::items_near(bot, bot.position(), 32.0)
+		.into_iter()
+		.map(|(entity, _)| entity)
+		.filter(|entity| {
+			bot.get_entity_component::<ItemItem>(*entity)
+				.is_some_and(|stack| stack.0.kind() == item)
+		})
+		.collect()
+}
+
+/// throws `count` of the item where it's looking, whole stacks first
+fn toss(bot: &Client, item: Item, count: i32) -> anyhow::Result<()> {
+	let inventory = bot.open_inventory().ok_or_else(|| {
+		anyhow!(
+			"{} has a container open, it can't throw anything",
+			bot.username()
+		)
+	})?;
+
+	let mut left = count;
+	for (slot, stack) in pathfind::inventory_slots(bot) {
+		if left <= 0 {
+			break;
+		}
+		if stack.kind() != item {
+			continue;
+		}
+		if stack.count() <= left {
+			inventory.click(ThrowClick::All { slot: slot as u16 });
+			left -= stack.count();
+		} else {
+			for _ in 0..left {
+				inventory.click(ThrowClick::Single { slot: slot as u16 });
+			}
+			left = 0;
+		}
+	}
+	Ok(())
+}
+
+fn player_pos(bot: &Client, name: &str) -> Option<Vec3> {
+	use azalea::{
+		GameProfileComponent,
+		entity::{Position, metadata::Player},
+	};
+	use bevy_ecs::prelude::With;
+
+	let entity =
+		bot.entity_by::<With<Player>, &GameProfileComponent>(|profile: &&GameProfileComponent| {
+			profile.name == name
+		})?;
+	let pos: Position = bot.get_entity_component(entity)?;
+	Some(pos.down(0.0))
+}
@@ -0,0 +1,83 @@
+use anyhow::anyhow;
+use azalea::{
+	BlockPos, BotClientExt, Client, Vec3,
+	blocks::{BlockState, BlockStates},
+	pathfinder::goals::{Goal, RadiusGoal},
+	prelude::PathfinderClientExt,
+	registry::Block,
+};
+
+pub const CROPS: &[Block] = &[
+	Block::Wheat,
+	Block::Carrots,
+	Block::Potatoes,
+	Block::Beetroots,
+];
+
+/// whether it's a crop that's done growing \
+/// age is the only property crops have, so the fully grown one is the last state of the block
+pub fn is_mature(state: BlockState) -> bool {
+	let block = Block::from(state);
+	CROPS.contains(&block)
+		&& BlockStates::from(block)
+			.set
+			.iter()
+			.map(|state| state.id())
+			.max() == Some(state.id())
+}
+
+/// breaks the crop, picks up what it dropped and plants it again
+pub async fn harvest(bot: &Client, pos: BlockPos, crop: Block) -> anyhow::Result<()> {
+	let goal = RadiusGoal {
+		pos: pos.center(),
+		radius: 3.5,
+	};
+	if !goal.success(bot.position().to_block_pos_floor()) {
+		bot.goto(goal).await;
+	}
+	bot.look_at(pos.center());
+	bot.mine(pos).await;
+
+	bot.goto(RadiusGoal {
+		pos: pos.center(),
+		radius: 1.0,
+	})
+	.await;
+	pathfind::wait_ticks(bot, 4).await;
+
+	replant(bot, pos, crop).await
+}
+
+/// plants the crop by clicking the top of the farmland under pos, never anything next to it
+async fn replant(bot: &Client, pos: BlockPos, crop: Block) -> anyhow::Result<()> {
+	let farmland = pos.down(1);
+	let soil = bot
+		.world()
+		.read()
+		.get_block_state(&farmland)
+		.map(Block::from);
+	if soil != Some(Block::Farmland) {
+		return Err(anyhow!("there's no farmland under {pos:?} to replant on"));
+	}
+
+	let seed = pathfind::item_for(crop)?;
+	if !pathfind::hold_item(bot, seed).await {
+		return Err(anyhow!("{} doesn't have any {seed:?}", bot.username()));
+	}
+	bot.look_at(Vec3::new(
+		farmland.x as f64 + 0.5,
+		farmland.y as f64 + 1.0,
+		farmland.z as f64 + 0.5,
+	));
+	// the face we're clicking comes from what we're looking at, which is only updated on the next tick
+	pathfind::wait_ticks(bot, 1).await;
+	bot.block_interact(farmland);
+	pathfind::wait_ticks(bot, 4).await;
+
+	match bot.world().read().get_block_state(&pos).map(Block::from) {
+		Some(block) if block == crop => Ok(()),
+		_ => Err(anyhow!(
+			"planted {crop:?} at {pos:?} but it never showed up"
+		)),
+	}
+}
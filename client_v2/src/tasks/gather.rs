@@ -0,0 +1,107 @@
+use std::{
+	collections::HashSet,
+	str::FromStr,
+	sync::LazyLock,
+	time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use azalea::{
+	BlockPos, Client,
+	blocks::BlockStates,
+	pathfinder::goals::{Goal, RadiusGoal},
+	prelude::PathfinderClientExt,
+	registry::{Block, Item},
+};
+
+/// blocks someone in this process is already mining for a gather task, so the others pick different ones \
+/// instances in other processes can still pick the same block but that's rare enough to not matter
+static CLAIMED: LazyLock<parking_lot::Mutex<HashSet<BlockPos>>> = LazyLock::new(Default::default);
+
+/// the blocks that drop the item when mined
+pub fn sources(item: Item) -> Vec<Block> {
+	let mut sources = match item {
+		Item::Cobblestone => vec![Block::Stone],
+		Item::CobbledDeepslate => vec![Block::Deepslate],
+		Item::Dirt => vec![Block::GrassBlock, Block::DirtPath],
+		Item::WheatSeeds => vec![Block::ShortGrass, Block::TallGrass],
+		Item::Flint => vec![Block::Gravel],
+		Item::Coal => vec![Block::CoalOre, Block::DeepslateCoalOre],
+		Item::RawIron => vec![Block::IronOre, Block::DeepslateIronOre],
+		Item::RawCopper => vec![Block::CopperOre, Block::DeepslateCopperOre],
+		Item::RawGold => vec![Block::GoldOre, Block::DeepslateGoldOre],
+		Item::Diamond => vec![Block::DiamondOre, Block::DeepslateDiamondOre],
+		Item::Emerald => vec![Block::EmeraldOre, Block::DeepslateEmeraldOre],
+		Item::LapisLazuli => vec![Block::LapisOre, Block::DeepslateLapisOre],
+		Item::Redstone => vec![Block::RedstoneOre, Block::DeepslateRedstoneOre],
+		Item::ClayBall => vec![Block::Clay],
+		Item::Snowball => vec![Block::SnowBlock, Block::Snow],
+		_ => vec![],
+	};
+	// most blocks just drop themselves
+	if let Ok(block) = Block::from_str(&item.to_string()) {
+		sources.push(block);
+	}
+	sources
+}
+
+/// mines the closest block that drops the item and picks up what it dropped
+pub async fn gather(bot: &Client, item: Item) -> anyhow::Result<()> {
+	let sources = sources(item);
+	if sources.is_empty() {
+		return Err(anyhow!("don't know where to get {item:?} from"));
+	}
+	let states = BlockStates {
+		set: sources
+			.into_iter()
+			.flat_map(|block| BlockStates::from(block).set)
+			.collect(),
+	};
+
+	let pos = {
+		let candidates = {
+			let world = bot.world();
+			let world = world.read();
+			world
+				.find_blocks(bot.eye_position(), &states)
+				.take(10)
+				.collect::<Vec<_>>()
+		};
+		let mut claimed = CLAIMED.lock();
+		let pos = candidates.into_iter().find(|pos| !claimed.contains(pos));
+		if let Some(pos) = pos {
+			claimed.insert(pos);
+		}
+		pos
+	}
+	.ok_or_else(|| anyhow!("{} can't see anything that drops {item:?}", bot.username()))?;
+
+	let res = mine_and_collect(bot, pos).await;
+	CLAIMED.lock().remove(&pos);
+	res
+}
+
+async fn mine_and_collect(bot: &Client, pos: BlockPos) -> anyhow::Result<()> {
+	let goal = RadiusGoal {
+		pos: pos.center(),
+		radius: 3.5,
+	};
+	if !goal.success(bot.position().to_block_pos_floor()) {
+		bot.goto(goal).await;
+	}
+	pathfind::mine_with_best_tool(bot, pos).await?;
+
+	// walk into the drops so they get picked up
+	let start = Instant::now();
+	bot.start_goto(RadiusGoal {
+		pos: pos.center(),
+		radius: 1.0,
+	});
+	while !bot.is_goto_target_reached() && start.elapsed() < Duration::from_secs(5) {
+		pathfind::wait_ticks(bot, 1).await;
+	}
+	bot.stop_pathfinding();
+	pathfind::wait_ticks(bot, 5).await;
+
+	Ok(())
+}
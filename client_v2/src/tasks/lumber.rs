@@ -0,0 +1,126 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use azalea::{
+	BlockPos, Client,
+	pathfinder::goals::{BlockPosGoal, RadiusGoal},
+	prelude::PathfinderClientExt,
+	registry::Block,
+};
+
+/// how far away a block can be mined from
+const REACH: f64 = 4.5;
+
+/// any kind of log or nether stem that's still part of a tree
+pub fn is_log(block: Block) -> bool {
+	let id = block.to_string();
+	!id.contains("stripped") && (id.ends_with("_log") || id.ends_with("_stem"))
+}
+pub fn is_leaves(block: Block) -> bool {
+	let id = block.to_string();
+	id.ends_with("_leaves") || id.ends_with("_wart_block")
+}
+
+/// the sapling that grows into the tree this log came from
+pub fn sapling_for(log: Block) -> Option<Block> {
+	match log {
+		Block::MangroveLog => Some(Block::MangrovePropagule),
+		Block::CrimsonStem => Some(Block::CrimsonFungus),
+		Block::WarpedStem => Some(Block::WarpedFungus),
+		_ => {
+			let id = log.to_string();
+			let sapling = id.strip_suffix("_log")?;
+			Block::from_str(&format!("{sapling}_sapling")).ok()
+		}
+	}
+}
+
+/// mines every log of the tree, pillaring up for the ones out of reach, then replants it
+pub async fn fell(
+	bot: &Client,
+	logs: &[BlockPos],
+	replant: &[(BlockPos, Block)],
+) -> anyhow::Result<()> {
+	let base = logs
+		.iter()
+		.min_by_key(|pos| pos.y)
+		.copied()
+		.ok_or_else(|| anyhow!("there's no tree to fell"))?;
+
+	let mut tries = 0;
+	loop {
+		let remaining = {
+			let world = bot.world();
+			let world = world.read();
+			logs.iter()
+				.filter(|pos| {
+					world
+						.get_block_state(pos)
+						.map(|state| is_log(Block::from(state)))
+						.unwrap_or(false)
+				})
+				.copied()
+				.collect::<Vec<_>>()
+		};
+		if remaining.is_empty() {
+			break;
+		}
+		tries += 1;
+		if tries > logs.len() * 2 + 10 {
+			return Err(anyhow!(
+				"gave up on a tree at {base:?} with {} logs left",
+				remaining.len()
+			));
+		}
+
+		let eyes = bot.eye_position();
+		let in_reach = remaining
+			.iter()
+			.filter(|pos| pos.center().distance_to(&eyes) <= REACH)
+			.min_by(|a, b| {
+				a.center()
+					.distance_to(&eyes)
+					.total_cmp(&b.center().distance_to(&eyes))
+			});
+		if let Some(pos) = in_reach {
+			pathfind::mine_with_best_tool(bot, *pos).await?;
+			pathfind::wait_ticks(bot, 2).await;
+			continue;
+		}
+
+		let feet = bot.position().to_block_pos_floor();
+		let lowest = remaining
+			.iter()
+			.min_by_key(|pos| pos.y)
+			.copied()
+			.unwrap_or(base);
+		if lowest.y <= feet.y + 2 {
+			bot.goto(RadiusGoal {
+				pos: lowest.center(),
+				radius: 3.5,
+			})
+			.await;
+		} else if feet.x != base.x || feet.z != base.z {
+			// the trunk's gone, so there's room to pillar up where it was
+			bot.goto(BlockPosGoal(base)).await;
+		} else {
+			pathfind::pillar_up(bot).await?;
+		}
+	}
+
+	// back down the pillar, picking up whatever fell on the way
+	if bot.position().to_block_pos_floor().y > base.y {
+		pathfind::dig_down(bot, base.y).await?;
+	}
+	pathfind::wait_ticks(bot, 20).await;
+
+	for (pos, sapling) in replant {
+		let item = pathfind::item_for(*sapling)?;
+		if pathfind::count_item(bot, item) == 0 {
+			continue;
+		}
+		pathfind::place_block(bot, *pos, *sapling).await?;
+	}
+
+	Ok(())
+}
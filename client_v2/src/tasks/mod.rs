@@ -1,4 +1,13 @@
+pub mod combat;
+pub mod craft;
+pub mod deliver;
+pub mod farm;
+pub mod gather;
+pub mod lumber;
 pub mod net;
+pub mod smelt;
+pub mod storage;
+pub mod survival;
 pub mod task;
 pub use task::Task;
 
@@ -1,20 +1,41 @@
+use std::sync::Arc;
+
 use anyhow::anyhow;
-use azalea::{Client, chat::ChatPacket};
+use azalea::{BlockPos, Client, chat::ChatPacket};
 use honeypack::{PacketRead, PacketWrite};
-use tokio::net::TcpStream;
+use tokio::{
+	net::{
+		TcpStream,
+		tcp::{OwnedReadHalf, OwnedWriteHalf},
+	},
+	sync::{Mutex, mpsc},
+};
 use uuid::Uuid;
 
-use crate::tasks::net::{
-	ClientboundHelloPacket, ClientboundPacket, ServerboundHelloPacket, ServerboundPacket,
+use crate::tasks::{
+	Task,
+	net::{
+		ClientboundHelloPacket, ClientboundPacket, ServerboundHelloPacket, ServerboundPacket,
+		TaskResult,
+	},
 };
 
-use super::hash_chat;
+use super::{hash_chat, report::WorldReports};
+
+/// the player inventory menu's main inventory and hotbar, see azalea::inventory::Player
+const MAIN_INVENTORY: std::ops::RangeInclusive<usize> = 9..=44;
+/// roughly how many blocks a single ReportArea packet is allowed to carry
+const SCAN_BLOCKS_PER_PACKET: i32 = 16 * 16 * 16;
 
 #[derive(Debug)]
 /// a client for communicating with a TasksHead
 pub struct Tasks {
 	inst_id: i32,
-	stream: TcpStream,
+	write: Arc<Mutex<OwnedWriteHalf>>,
+	/// the read half, until the first `next` hands it to a reader along with the bot
+	reader: Option<(OwnedReadHalf, mpsc::UnboundedSender<Option<Task>>)>,
+	/// tasks the reader got from the server
+	assigned: mpsc::UnboundedReceiver<Option<Task>>,
 }
 impl Tasks {
 	/// there's no settings because the server pretty much just tells the client who it is \
@@ -23,71 +44,91 @@ impl Tasks {
 		let mut stream = TcpStream::connect(super::ADDR).await?;
 		println!("client connected to {}", super::ADDR);
 
-		let hello = ServerboundHelloPacket { lucky_number: 6 };
+		let hello = ServerboundHelloPacket {
+			lucky_number: 6,
+			pid: std::process::id(),
+		};
 		stream.write_as_packet(hello).await?;
 
 		let hello: ClientboundHelloPacket = stream.read_as_packet().await?;
 
+		let (read, write) = stream.into_split();
+		let (sender, assigned) = mpsc::unbounded_channel();
 		Ok((
 			hello.inst_id,
 			hello.name,
 			Self {
 				inst_id: hello.inst_id,
-				stream,
+				write: Arc::new(Mutex::new(write)),
+				reader: Some((read, sender)),
+				assigned,
 			},
 		))
 	}
 
-	pub async fn next(&mut self, bot: &Client) -> anyhow::Result<crate::tasks::Task> {
+	pub async fn next(&mut self, bot: &Client) -> anyhow::Result<Task> {
+		if let Some((read, assigned)) = self.reader.take() {
+			let (bot, write) = (bot.clone(), self.write.clone());
+			tokio::spawn(async move {
+				if let Err(err) = read_packets(read, bot, write, assigned).await {
+					eprintln!("stopped reading from the server: {err}");
+				}
+			});
+		}
+
 		let request = ServerboundPacket::RequestTask {
 			inst_id: self.inst_id,
 		};
-		self.stream.write_as_packet(request).await?;
+		self.write.lock().await.write_as_packet(request).await?;
 
-		loop {
-			let response: ClientboundPacket = self.stream.read_as_packet().await?;
-			match response {
-				ClientboundPacket::AssignTask(task) => {
-					break task.ok_or_else(|| anyhow!("task is None"));
+		match self.assigned.recv().await {
+			Some(task) => task.ok_or_else(|| anyhow!("task is None")),
+			None => Err(anyhow!("lost the connection to the server")),
+		}
+	}
+	/// reports how the last task went, so tasks depending on it can go ahead
+	pub async fn done(&mut self, result: TaskResult) -> anyhow::Result<()> {
+		let packet = ServerboundPacket::TaskDone {
+			inst_id: self.inst_id,
+			result,
+		};
+		self.write.lock().await.write_as_packet(packet).await?;
+		Ok(())
+	}
+	/// tells the server what the bot has on it
+	pub async fn report_inventory(&mut self, bot: &Client) -> anyhow::Result<()> {
+		let mut items = Vec::<(azalea::registry::Item, i32)>::new();
+		let mut free_slots = 0;
+		for (slot, stack) in pathfind::inventory_slots(bot) {
+			if !stack.is_present() {
+				// the crafting grid and armor slots come before the inventory
+				if MAIN_INVENTORY.contains(&slot) {
+					free_slots += 1;
 				}
-				_ => self.handle_other(response, bot).await?,
+				continue;
+			}
+			match items.iter_mut().find(|(item, _)| *item == stack.kind()) {
+				Some((_, count)) => *count += stack.count(),
+				None => items.push((stack.kind(), stack.count())),
 			}
 		}
+
+		let packet = ServerboundPacket::ReportInventory {
+			inst_id: self.inst_id,
+			items,
+			free_slots,
+		};
+		self.write.lock().await.write_as_packet(&packet).await?;
+		Ok(())
 	}
-	pub async fn handle_other(
-		&mut self,
-		packet: ClientboundPacket,
-		bot: &Client,
-	) -> anyhow::Result<()> {
-		match packet {
-			ClientboundPacket::Find { username } => {
-				use azalea::{
-					GameProfileComponent,
-					entity::{Position, metadata::Player},
-				};
-				use bevy_ecs::prelude::With;
-
-				let entity = bot.entity_by::<With<Player>, &GameProfileComponent>(
-					|profile: &&GameProfileComponent| profile.name == username,
-				);
-				let report = if let Some(player) = entity {
-					let pos: Option<Position> = bot.get_entity_component(player);
-					if let Some(pos) = pos {
-						crate::tasks::net::PosReport::Found(pos.down(0.0))
-					} else {
-						crate::tasks::net::PosReport::NotHere
-					}
-				} else {
-					crate::tasks::net::PosReport::NotHere
-				};
-				let report = ServerboundPacket::ReportPosition { username, report };
-				self.stream.write_as_packet(report).await?;
-			}
-			ClientboundPacket::AssignTask(_) => {}
+	/// tells the server what was in the containers the bots in this process opened
+	pub async fn report_containers(&mut self) -> anyhow::Result<()> {
+		for (pos, items) in crate::tasks::storage::take_opened() {
+			let packet = ServerboundPacket::ReportContainer { pos, items };
+			self.write.lock().await.write_as_packet(&packet).await?;
 		}
 		Ok(())
 	}
-
 	pub async fn tick(&mut self, bot: &azalea::Client) -> anyhow::Result<()> {
 		Ok(())
 	}
@@ -100,14 +141,169 @@ impl Tasks {
 			sender,
 			content,
 		};
-		self.stream.write_as_packet(&packet).await?;
+		self.write.lock().await.write_as_packet(&packet).await?;
+
+		Ok(())
+	}
+	/// sends the world changes that piled up since the last report
+	pub async fn report_world(
+		&mut self,
+		bot: &Client,
+		reports: &parking_lot::Mutex<WorldReports>,
+	) -> anyhow::Result<()> {
+		let (blocks, chunks) = {
+			let world = bot.world();
+			let world = world.read();
+			reports.lock().take(&world)
+		};
+
+		if !blocks.is_empty() {
+			let packet = ServerboundPacket::ReportBlocks { blocks };
+			self.write.lock().await.write_as_packet(&packet).await?;
+		}
+		for chunk in chunks {
+			let packet = ServerboundPacket::ReportChunk(chunk);
+			self.write.lock().await.write_as_packet(&packet).await?;
+		}
+
+		Ok(())
+	}
+	pub async fn report_vitals(
+		&mut self,
+		vitals: crate::tasks::survival::Vitals,
+	) -> anyhow::Result<()> {
+		let packet = ServerboundPacket::ReportVitals {
+			inst_id: self.inst_id,
+			vitals,
+		};
+		self.write.lock().await.write_as_packet(&packet).await?;
+		Ok(())
+	}
+	pub async fn died(&mut self, pos: azalea::Vec3) -> anyhow::Result<()> {
+		let packet = ServerboundPacket::Died {
+			inst_id: self.inst_id,
+			pos,
+		};
+		self.write.lock().await.write_as_packet(&packet).await?;
+		Ok(())
+	}
+	pub async fn entity_gone(&mut self, uuid: Uuid) -> anyhow::Result<()> {
+		let packet = ServerboundPacket::EntityGone { uuid };
+		self.write.lock().await.write_as_packet(&packet).await?;
 
 		Ok(())
 	}
-	pub async fn agro(&mut self, uuid: Uuid) -> anyhow::Result<()> {
-		let packet = ServerboundPacket::Agro { uuid };
-		self.stream.write_as_packet(&packet).await?;
+	pub async fn agro(
+		&mut self,
+		uuid: Uuid,
+		player: Option<String>,
+		damage: f32,
+	) -> anyhow::Result<()> {
+		let packet = ServerboundPacket::Agro {
+			uuid,
+			player,
+			damage,
+		};
+		self.write.lock().await.write_as_packet(&packet).await?;
 
 		Ok(())
 	}
 }
+
+/// reads everything the server sends, answering its requests right away even while the bot is busy with a task
+async fn read_packets(
+	mut read: OwnedReadHalf,
+	bot: Client,
+	write: Arc<Mutex<OwnedWriteHalf>>,
+	assigned: mpsc::UnboundedSender<Option<Task>>,
+) -> anyhow::Result<()> {
+	loop {
+		let packet: ClientboundPacket = read.read_as_packet().await?;
+		match packet {
+			ClientboundPacket::AssignTask(task) => assigned.send(task)?,
+			_ => handle_other(packet, &bot, &write).await?,
+		}
+	}
+}
+
+async fn handle_other(
+	packet: ClientboundPacket,
+	bot: &Client,
+	write: &Mutex<OwnedWriteHalf>,
+) -> anyhow::Result<()> {
+	match packet {
+		ClientboundPacket::Find { username } => {
+			use azalea::{
+				GameProfileComponent,
+				entity::{Position, metadata::Player},
+			};
+			use bevy_ecs::prelude::With;
+
+			let entity = bot.entity_by::<With<Player>, &GameProfileComponent>(
+				|profile: &&GameProfileComponent| profile.name == username,
+			);
+			let report = if let Some(player) = entity {
+				let pos: Option<Position> = bot.get_entity_component(player);
+				if let Some(pos) = pos {
+					crate::tasks::net::PosReport::Found(pos.down(0.0))
+				} else {
+					crate::tasks::net::PosReport::NotHere
+				}
+			} else {
+				crate::tasks::net::PosReport::NotHere
+			};
+			let report = ServerboundPacket::ReportPosition { username, report };
+			write.lock().await.write_as_packet(report).await?;
+		}
+		ClientboundPacket::Scan { from, to } => {
+			// report a few layers at a time so a big scan doesn't turn into one huge packet
+			let (min_y, max_y) = (from.y.min(to.y), from.y.max(to.y));
+			let area = ((from.x - to.x).abs() + 1) * ((from.z - to.z).abs() + 1);
+			let layers = (SCAN_BLOCKS_PER_PACKET / area).max(1);
+
+			for y in (min_y..=max_y).step_by(layers as usize) {
+				let from = BlockPos::new(from.x, y, from.z);
+				let to = BlockPos::new(to.x, (y + layers - 1).min(max_y), to.z);
+				let states = {
+					let world = bot.world();
+					let world = world.read();
+					super::cuboid(from, to)
+						.map(|pos| world.get_block_state(&pos).map(|state| state.id() as u32))
+						.collect::<Vec<_>>()
+				};
+				let report = ServerboundPacket::ReportArea { from, to, states };
+				write.lock().await.write_as_packet(report).await?;
+			}
+		}
+		ClientboundPacket::FindMobs { center, radius } => {
+			use azalea::{
+				entity::{Dead, EntityUuid, Position, metadata::AbstractMonster},
+				world::InstanceName,
+			};
+			use bevy_ecs::prelude::{With, Without};
+
+			let instance = bot.component::<InstanceName>();
+			let mobs = {
+				let mut ecs = bot.ecs.lock();
+				let mut query = ecs.query_filtered::<
+					(&EntityUuid, &Position, &InstanceName),
+					(With<AbstractMonster>, Without<Dead>),
+				>();
+				query
+					.iter(&ecs)
+					.filter(|(_, pos, world)| {
+						**world == instance && pos.distance_to(&center) <= radius
+					})
+					.map(|(uuid, pos, _)| (**uuid, **pos))
+					.collect::<Vec<_>>()
+			};
+			let report = ServerboundPacket::ReportMobs { mobs };
+			write.lock().await.write_as_packet(report).await?;
+		}
+		ClientboundPacket::Combat { username, style } => {
+			crate::tasks::combat::set_style(username, style);
+		}
+		ClientboundPacket::AssignTask(_) => {}
+	}
+	Ok(())
+}
@@ -1,11 +1,12 @@
 // this module contains all the code for cross-process swarm coordination
 
 pub mod client;
+pub mod report;
 pub mod server;
 
 use std::borrow::Cow;
 
-use azalea::{Vec3, chat::ChatPacket, core::math::lcm};
+use azalea::{BlockPos, Vec3, chat::ChatPacket, core::math::lcm};
 pub use client::Tasks;
 pub use server::start_server;
 use uuid::Uuid;
@@ -25,6 +26,8 @@ pub const ADDR: &str = "127.0.0.1:8789";
 pub struct ServerboundHelloPacket {
 	/// not used for anything but something shits itself if we send empty packets
 	lucky_number: i32,
+	/// instances in the same process share their world, so the server only asks one of them about it
+	pid: u32,
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -41,14 +44,148 @@ pub enum ServerboundPacket {
 		sender: Option<String>,
 		content: String,
 	},
-	/// signals others to attack the entity with the given uuid
-	Agro { uuid: Uuid },
+	/// something hurt this instance, the coordinator decides whether everyone attacks it
+	Agro {
+		uuid: Uuid,
+		/// the name of the player that did it, None if it's not a player
+		player: Option<String>,
+		damage: f32,
+	},
 
 	/// requests the next task for this instance \
 	/// server will return ClientboundPacket::AssignTask
 	RequestTask { inst_id: i32 },
+	/// the instance is done executing the last task it was assigned
+	TaskDone { inst_id: i32, result: TaskResult },
 	/// reports the owner's position to everyone else
 	ReportPosition { username: String, report: PosReport },
+	/// response to ClientboundPacket::Scan \
+	/// block state ids in the order of `cuboid(from, to)`, None if the chunk isn't loaded
+	ReportArea {
+		from: BlockPos,
+		to: BlockPos,
+		states: Vec<Option<u32>>,
+	},
+	/// block state ids of blocks that changed since the last report
+	ReportBlocks { blocks: Vec<(BlockPos, u32)> },
+	/// a chunk one of the instances just loaded
+	ReportChunk(ChunkReport),
+	/// an entity died or despawned near one of the instances, nobody has to fight it anymore
+	EntityGone { uuid: Uuid },
+	/// response to ClientboundPacket::FindMobs
+	ReportMobs { mobs: Vec<(Uuid, Vec3)> },
+	/// the instance died there, whatever it was doing goes to someone else
+	Died { inst_id: i32, pos: Vec3 },
+	/// the instance's health and hunger, sent whenever they change
+	ReportVitals {
+		inst_id: i32,
+		vitals: crate::tasks::survival::Vitals,
+	},
+	/// what was in a container one of the instances opened, as it was closed
+	ReportContainer {
+		pos: BlockPos,
+		items: Vec<(azalea::registry::Item, i32)>,
+	},
+	/// everything in the instance's inventory, sent after every task
+	ReportInventory {
+		inst_id: i32,
+		items: Vec<(azalea::registry::Item, i32)>,
+		/// empty slots in the main inventory and hotbar
+		free_slots: i32,
+	},
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ChunkReport {
+	pub x: i32,
+	pub z: i32,
+	pub min_y: i32,
+	/// how many sections the chunk has
+	pub height: i32,
+	/// only the sections that aren't all air, the rest are air
+	pub sections: Vec<SectionReport>,
+}
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SectionReport {
+	/// counted from the bottom of the chunk
+	pub y: i32,
+	/// block state ids, None for blocks the client didn't know
+	pub palette: Vec<Option<u32>>,
+	/// bits per palette index
+	pub bits: u8,
+	/// palette indices in y, z, x order (x is the innermost), `bits` each and packed into u64s \
+	/// empty if the whole section is palette[0]
+	pub blocks: Vec<u64>,
+}
+impl SectionReport {
+	pub fn new(y: i32, palette: Vec<Option<u32>>, indices: &[u16]) -> Self {
+		if palette.len() <= 1 {
+			return Self {
+				y,
+				palette,
+				bits: 0,
+				blocks: Vec::new(),
+			};
+		}
+
+		let bits = (usize::BITS - (palette.len() - 1).leading_zeros()) as u8;
+		let per_long = 64 / bits as usize;
+		let blocks = indices
+			.chunks(per_long)
+			.map(|indices| {
+				indices.iter().enumerate().fold(0, |long, (i, index)| {
+					long | ((*index as u64) << (i * bits as usize))
+				})
+			})
+			.collect();
+
+		Self {
+			y,
+			palette,
+			bits,
+			blocks,
+		}
+	}
+
+	/// unpacks `blocks`, None if the whole section is palette[0]
+	pub fn indices(&self) -> Option<Vec<u16>> {
+		if self.blocks.is_empty() {
+			return None;
+		}
+
+		let bits = self.bits as usize;
+		let per_long = 64 / bits;
+		let mask = (1 << bits) - 1;
+		Some(
+			self.blocks
+				.iter()
+				.flat_map(|long| (0..per_long).map(move |i| ((long >> (i * bits)) & mask) as u16))
+				.take(4096)
+				.collect(),
+		)
+	}
+}
+
+#[test]
+fn packs_section_indices() {
+	let indices = (0..4096).map(|i| (i % 5) as u16).collect::<Vec<_>>();
+	let section = SectionReport::new(0, vec![Some(0), Some(1), Some(2), Some(3), None], &indices);
+	assert_eq!(section.bits, 3);
+	assert_eq!(section.indices(), Some(indices));
+
+	let section = SectionReport::new(0, vec![Some(1)], &[0; 4096]);
+	assert_eq!(section.indices(), None);
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum TaskResult {
+	Done,
+	/// the task will be handed out again, until it fails too many times
+	Failed(String),
+	/// the bot stopped to eat or run away, the task is handed out again without it counting as a failure
+	Interrupted(String),
+	/// the bot doesn't have a tool that can break the block, someone else gets the task
+	NoTool(String),
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -63,6 +200,21 @@ pub enum ClientboundPacket {
 	Find {
 		username: String,
 	},
+	/// client responds with ServerboundPacket::ReportArea
+	Scan {
+		from: BlockPos,
+		to: BlockPos,
+	},
+	/// client responds with ServerboundPacket::ReportMobs
+	FindMobs {
+		center: Vec3,
+		radius: f64,
+	},
+	/// changes how one bot fights, or every bot if there's no username
+	Combat {
+		username: Option<String>,
+		style: crate::tasks::combat::CombatStyle,
+	},
 	AssignTask(Option<Task>),
 }
 
@@ -83,3 +235,17 @@ pub fn hash_chat(m: &ChatPacket) -> u64 {
 
 	hasher.finish()
 }
+
+/// every position between from and to, x being the innermost and y the outermost loop \
+/// it's the order areas are reported in, so both sides have to use this
+pub fn cuboid(from: BlockPos, to: BlockPos) -> impl Iterator<Item = BlockPos> {
+	let min = BlockPos::new(from.x.min(to.x), from.y.min(to.y), from.z.min(to.z));
+	let max = BlockPos::new(from.x.max(to.x), from.y.max(to.y), from.z.max(to.z));
+
+	(min.y..max.y + 1)
+		.map(move |y| {
+			(min.z..max.z + 1).map(move |z| (min.x..max.x + 1).map(move |x| BlockPos { x, y, z }))
+		})
+		.flatten()
+		.flatten()
+}
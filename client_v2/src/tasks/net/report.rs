@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet};
+
+use azalea::{BlockPos, blocks::BlockState, core::position::ChunkPos, world::Instance};
+
+use super::{ChunkReport, SectionReport};
+
+/// world changes the instances of a process saw, waiting to be sent to the server \
+/// every instance in the process shares one of these, since they share their world too and
+/// the same block update would be sent by every one of them otherwise
+#[derive(Debug, Default)]
+pub struct WorldReports {
+	blocks: HashMap<BlockPos, u32>,
+	/// chunks that were loaded but not reported yet
+	chunks: HashSet<ChunkPos>,
+	reported: HashSet<ChunkPos>,
+}
+impl WorldReports {
+	pub fn block(&mut self, pos: BlockPos, state: BlockState) {
+		self.blocks.insert(pos, state.id() as u32);
+	}
+	pub fn chunk_loaded(&mut self, pos: ChunkPos) {
+		if !self.reported.contains(&pos) {
+			self.chunks.insert(pos);
+		}
+	}
+	pub fn chunk_unloaded(&mut self, pos: ChunkPos) {
+		self.chunks.remove(&pos);
+		self.reported.remove(&pos);
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.blocks.is_empty() && self.chunks.is_empty()
+	}
+
+	/// takes the block updates and the chunks that are in the world by now
+	pub fn take(&mut self, world: &Instance) -> (Vec<(BlockPos, u32)>, Vec<ChunkReport>) {
+		let blocks = std::mem::take(&mut self.blocks).into_iter().collect();
+
+		let mut chunks = Vec::new();
+		for pos in self.chunks.iter().copied().collect::<Vec<_>>() {
+			// the packet might've arrived before the chunk was actually added to the world
+			if let Some(report) = report_chunk(world, pos) {
+				self.chunks.remove(&pos);
+				self.reported.insert(pos);
+				chunks.push(report);
+			}
+		}
+
+		(blocks, chunks)
+	}
+}
+
+fn report_chunk(world: &Instance, pos: ChunkPos) -> Option<ChunkReport> {
+	let min_y = world.chunks.min_y;
+	let height = world.chunks.height as i32;
+
+	let origin = BlockPos::new(pos.x * 16, min_y, pos.z * 16);
+	world.get_block_state(&origin)?;
+
+	let sections = (0..height / 16)
+		.filter_map(|section| {
+			let mut palette: Vec<Option<u32>> = Vec::new();
+			let indices = (0..4096)
+				.map(|i: i32| {
+					let at = BlockPos::new(
+						origin.x + (i & 15),
+						min_y + section * 16 + (i >> 8),
+						origin.z + ((i >> 4) & 15),
+					);
+					let state = world.get_block_state(&at).map(|state| state.id() as u32);
+					match palette.iter().position(|id| *id == state) {
+						Some(index) => index as u16,
+						None => {
+							palette.push(state);
+							(palette.len() - 1) as u16
+						}
+					}
+				})
+				.collect::<Vec<_>>();
+
+			// the server assumes air for any section that isn't reported
+			if palette.iter().all(|state| {
+				state.is_some_and(|id| BlockState::try_from(id).is_ok_and(|state| state.is_air()))
+			}) {
+				return None;
+			}
+			Some(SectionReport::new(section, palette, &indices))
+		})
+		.collect();
+
+	Some(ChunkReport {
+		x: pos.x,
+		z: pos.z,
+		min_y,
+		height: height / 16,
+		sections,
+	})
+}
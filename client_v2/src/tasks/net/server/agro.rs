@@ -0,0 +1,125 @@
+use std::{
+	collections::{HashMap, HashSet},
+	time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use uuid::Uuid;
+
+/// who the swarm fights back against, so it never turns on the owner or itself
+#[derive(Clone, Debug)]
+pub struct AgroPolicy {
+	/// never attacked, on top of the owner and the bots
+	pub friends: HashSet<String>,
+	/// always fought back against, whatever the rules say
+	pub foes: HashSet<String>,
+	/// fight back against players that aren't friends
+	pub players: bool,
+	/// fight back against mobs
+	pub mobs: bool,
+	/// hits that did less damage than this are ignored
+	pub min_damage: f32,
+	/// the same attacker can only set the swarm off once every this long
+	pub cooldown: Duration,
+	/// how long an attacker is fought after it last hurt someone
+	pub expiry: Duration,
+	/// nothing sets the swarm off until then, see `gang peace`
+	peace_until: Option<Instant>,
+	/// uuid -> the last time it set the swarm off
+	last_agro: HashMap<Uuid, Instant>,
+}
+impl Default for AgroPolicy {
+	fn default() -> Self {
+		Self {
+			friends: HashSet::new(),
+			foes: HashSet::new(),
+			players: true,
+			mobs: true,
+			min_damage: 1.0,
+			cooldown: Duration::from_secs(1),
+			expiry: Duration::from_secs(30),
+			peace_until: None,
+			last_agro: HashMap::new(),
+		}
+	}
+}
+impl AgroPolicy {
+	/// whether the swarm should go after whatever hurt one of the bots \
+	/// `allies` being the owner and every bot's username
+	pub fn should_agro<'a>(
+		&mut self,
+		uuid: Uuid,
+		player: Option<&str>,
+		damage: f32,
+		allies: impl IntoIterator<Item = &'a str>,
+	) -> bool {
+		let mut allies = allies
+			.into_iter()
+			.chain(self.friends.iter().map(|a| a.as_str()));
+		let is_ally = allies
+			.any(|ally| player == Some(ally) || azalea::auth::offline::generate_uuid(ally) == uuid);
+		if is_ally {
+			return false;
+		}
+		if self.peace_until.is_some_and(|until| Instant::now() < until) {
+			return false;
+		}
+
+		let foe = player.map(|name| self.foes.contains(name)).unwrap_or(false);
+		if !foe {
+			let allowed = match player {
+				Some(_) => self.players,
+				None => self.mobs,
+			};
+			if !allowed || damage < self.min_damage {
+				return false;
+			}
+		}
+
+		if let Some(last) = self.last_agro.get(&uuid) {
+			if last.elapsed() < self.cooldown {
+				return false;
+			}
+		}
+		self.last_agro.insert(uuid, Instant::now());
+		true
+	}
+
+	/// ignores every hit for a while, so the swarm can walk away from a fight
+	pub fn peace(&mut self, duration: Duration) {
+		self.peace_until = Some(Instant::now() + duration);
+		self.last_agro.clear();
+	}
+
+	/// `gang agro <setting> <value>`
+	pub fn set(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+		let on = || match value {
+			"on" => Ok(true),
+			"off" => Ok(false),
+			_ => Err(anyhow!("expected on or off, got {value}")),
+		};
+		match key {
+			"players" => self.players = on()?,
+			"mobs" => self.mobs = on()?,
+			"damage" => self.min_damage = value.parse()?,
+			"cooldown" => self.cooldown = Duration::from_secs_f32(value.parse()?),
+			"expiry" => self.expiry = Duration::from_secs_f32(value.parse()?),
+			_ => return Err(anyhow!("there's no agro setting called {key}")),
+		}
+		Ok(())
+	}
+
+	pub fn describe(&self) -> String {
+		let on = |a| if a { "on" } else { "off" };
+		format!(
+			"players {}, mobs {}, damage {}, cooldown {}s, expiry {}s, {} friends, {} foes",
+			on(self.players),
+			on(self.mobs),
+			self.min_damage,
+			self.cooldown.as_secs_f32(),
+			self.expiry.as_secs_f32(),
+			self.friends.len(),
+			self.foes.len()
+		)
+	}
+}
@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+
+use azalea::{BlockPos, registry::Block};
+
+use crate::tasks::{Task, net::cuboid};
+
+/// how many times a task can fail before we give up on it and let the tasks depending on it go ahead
+const MAX_FAILURES: u8 = 3;
+
+pub type NodeId = u64;
+
+/// a set of tasks with ordering constraints between them, not scheduled yet \
+/// tasks are referred to by the index `push` returned
+#[derive(Clone, Debug, Default)]
+pub struct Job {
+	tasks: Vec<(Task, Vec<usize>)>,
+}
+impl Job {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// adds a task that can start right away
+	pub fn push(&mut self, task: Task) -> usize {
+		self.push_after(task, [])
+	}
+	/// adds a task that can only start once every task in `after` is done
+	pub fn push_after(&mut self, task: Task, after: impl IntoIterator<Item = usize>) -> usize {
+		let after = after
+			.into_iter()
+			.filter(|i| *i < self.tasks.len())
+			.collect();
+		self.tasks.push((task, after));
+		self.tasks.len() - 1
+	}
+	/// makes `task` wait for `after` too
+	pub fn depend(&mut self, task: usize, after: usize) {
+		if after < self.tasks.len() && after != task {
+			if let Some((_, deps)) = self.tasks.get_mut(task) {
+				deps.push(after);
+			}
+		}
+	}
+
+	pub fn task(&self, i: usize) -> Option<&Task> {
+		self.tasks.get(i).map(|(task, _)| task)
+	}
+	pub fn len(&self) -> usize {
+		self.tasks.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.tasks.is_empty()
+	}
+
+	/// mines every block in `positions`, every block only after the one right above it
+	pub fn mine(positions: impl IntoIterator<Item = BlockPos>) -> Self {
+		let mut job = Self::new();
+		job.mine_all(positions);
+		job
+	}
+	/// adds the tasks of `Job::mine` to this job, returns pos -> index of its Mine task
+	pub fn mine_all(
+		&mut self,
+		positions: impl IntoIterator<Item = BlockPos>,
+	) -> HashMap<BlockPos, usize> {
+		let mut positions = positions.into_iter().collect::<Vec<_>>();
+		positions.sort_by_key(|pos| -pos.y);
+
+		let mut indices = HashMap::new();
+		for pos in positions {
+			let above = indices.get(&pos.up(1)).copied();
+			let i = self.push_after(Task::Mine(pos), above);
+			indices.insert(pos, i);
+		}
+		indices
+	}
+	/// places every block bottom to top, every block only after the one right below it \
+	/// and after whatever's mined out of its way, `mines` being what `mine_all` returned
+	pub fn place_all(
+		&mut self,
+		blocks: impl IntoIterator<Item = (BlockPos, Block)>,
+		mines: &HashMap<BlockPos, usize>,
+	) -> HashMap<BlockPos, usize> {
+		let mut blocks = blocks.into_iter().collect::<Vec<_>>();
+		blocks.sort_by_key(|(pos, _)| pos.y);
+
+		let mut indices = HashMap::new();
+		for (pos, block) in blocks {
+			let below = indices.get(&pos.down(1)).copied();
+			let mined = mines.get(&pos).copied();
+			let i = self.push_after(Task::Place { pos, block }, below.into_iter().chain(mined));
+			indices.insert(pos, i);
+		}
+		indices
+	}
+	/// mines the whole cuboid top to bottom
+	pub fn demolish(from: BlockPos, to: BlockPos) -> Self {
+		Self::mine(cuboid(from, to))
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum NodeState {
+	Waiting,
+	Leased(i32),
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+	task: Task,
+	/// the number of prerequisites that aren't done yet
+	waiting_on: usize,
+	dependents: Vec<NodeId>,
+	state: NodeState,
+	failures: u8,
+	/// instances that can't do the task, it's never handed to them again
+	excluded: HashSet<i32>,
+}
+
+/// every scheduled task, with the dependencies between them \
+/// tasks only get handed out once everything they depend on is done, and are removed once they're done
+#[derive(Debug, Default)]
+pub struct TaskGraph {
+	nodes: HashMap<NodeId, Node>,
+	next_id: NodeId,
+	/// inst_id -> the node it's working on
+	leased: HashMap<i32, NodeId>,
+}
+impl TaskGraph {
+	/// adds every task in the job, returns their ids in the order they were pushed
+	pub fn add(&mut self, job: Job) -> Vec<NodeId> {
+		let ids = (0..job.tasks.len())
+			.map(|i| self.next_id + i as NodeId)
+			.collect::<Vec<_>>();
+		self.next_id += job.tasks.len() as NodeId;
+
+		for (i, (task, deps)) in job.tasks.into_iter().enumerate() {
+			self.nodes.insert(
+				ids[i],
+				Node {
+					task,
+					waiting_on: deps.len(),
+					dependents: Vec::new(),
+					state: NodeState::Waiting,
+					failures: 0,
+					excluded: HashSet::new(),
+				},
+			);
+			for dep in deps {
+				if let Some(dep) = self.nodes.get_mut(&ids[dep]) {
+					dep.dependents.push(ids[i]);
+				}
+			}
+		}
+		ids
+	}
+
+	pub fn task(&self, id: NodeId) -> Option<&Task> {
+		self.nodes.get(&id).map(|node| &node.task)
+	}
+	pub fn contains(&self, id: NodeId) -> bool {
+		self.nodes.contains_key(&id)
+	}
+	/// whether the task could be handed out right now
+	pub fn is_ready(&self, id: NodeId) -> bool {
+		self.nodes
+			.get(&id)
+			.map(|node| node.waiting_on == 0 && node.state == NodeState::Waiting)
+			.unwrap_or(false)
+	}
+
+	/// whether the task could be handed to this instance right now
+	pub fn is_ready_for(&self, id: NodeId, inst_id: i32) -> bool {
+		self.is_ready(id)
+			&& self
+				.nodes
+				.get(&id)
+				.map(|node| !node.excluded.contains(&inst_id))
+				.unwrap_or(false)
+	}
+
+	/// hands the task to the instance, it'll be done once the instance reports back
+	pub fn lease(&mut self, id: NodeId, inst_id: i32) -> Option<Task> {
+		if !self.is_ready_for(id, inst_id) {
+			return None;
+		}
+		self.release(inst_id);
+
+		let node = self.nodes.get_mut(&id)?;
+		node.state = NodeState::Leased(inst_id);
+		self.leased.insert(inst_id, id);
+		Some(node.task.clone())
+	}
+	/// the task the instance is working on, if it's one of ours
+	pub fn leased_by(&self, inst_id: i32) -> Option<NodeId> {
+		self.leased.get(&inst_id).copied()
+	}
+
+	/// the instance finished its task, returns the task
+	pub fn done(&mut self, inst_id: i32) -> Option<Task> {
+		let id = self.leased.remove(&inst_id)?;
+		self.complete(id)
+	}
+	/// the instance couldn't finish its task, it goes back to be retried by someone \
+	/// returns the task if we gave up on it instead
+	pub fn failed(&mut self, inst_id: i32) -> Option<Task> {
+		let id = self.leased.remove(&inst_id)?;
+		let node = self.nodes.get_mut(&id)?;
+		node.failures += 1;
+		node.state = NodeState::Waiting;
+		if node.failures >= MAX_FAILURES {
+			self.complete(id)
+		} else {
+			None
+		}
+	}
+	/// the instance won't be working on its task anymore, without it being its fault \
+	/// returns the task's id so it can be put back in a region
+	pub fn release(&mut self, inst_id: i32) -> Option<NodeId> {
+		let id = self.leased.remove(&inst_id)?;
+		if let Some(node) = self.nodes.get_mut(&id) {
+			node.state = NodeState::Waiting;
+		}
+		Some(id)
+	}
+	/// the instance can't do its task, someone else has to \
+	/// returns how many instances can't do it so far
+	pub fn exclude(&mut self, inst_id: i32) -> usize {
+		let Some(id) = self.leased_by(inst_id) else {
+			return 0;
+		};
+		match self.nodes.get_mut(&id) {
+			Some(node) => {
+				node.excluded.insert(inst_id);
+				node.excluded.len()
+			}
+			None => 0,
+		}
+	}
+
+	/// every instance gets another go at the tasks it couldn't do, after one of them got a new tool
+	pub fn clear_exclusions(&mut self) {
+		for node in self.nodes.values_mut() {
+			node.excluded.clear();
+		}
+	}
+
+	/// removes the task and lets whatever depends on it go ahead, returns the task
+	pub fn complete(&mut self, id: NodeId) -> Option<Task> {
+		let node = self.nodes.remove(&id)?;
+		if let NodeState::Leased(inst_id) = node.state {
+			self.leased.remove(&inst_id);
+		}
+		for dependent in node.dependents {
+			if let Some(dependent) = self.nodes.get_mut(&dependent) {
+				dependent.waiting_on = dependent.waiting_on.saturating_sub(1);
+			}
+		}
+		Some(node.task)
+	}
+
+	pub fn clear(&mut self) {
+		self.nodes.clear();
+		self.leased.clear();
+	}
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+}
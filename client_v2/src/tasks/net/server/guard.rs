@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use azalea::Vec3;
+use uuid::Uuid;
+
+use super::targets::Targets;
+
+/// how long a mob is remembered after the last report, longer than most tasks take \
+/// so a guard that was busy with one still goes after it once it's done
+const MOB_MEMORY: Duration = Duration::from_secs(60);
+
+#[derive(Copy, Clone, Debug)]
+pub enum Center {
+	Owner,
+	Pos(Vec3),
+}
+
+/// bots standing around a point, attacking whatever hostile mob comes close
+#[derive(Clone, Debug)]
+pub struct Guard {
+	pub center: Center,
+	pub radius: f64,
+	/// hostile mobs inside the radius, forgotten soon after nobody reports them anymore
+	pub mobs: Targets,
+	/// whether the owner's been out of sight for too long to guard them, so it's only said once
+	pub lost_center: bool,
+}
+impl Guard {
+	pub fn new(center: Center, radius: f64) -> Self {
+		Self {
+			center,
+			radius,
+			mobs: Targets::new(MOB_MEMORY, 3),
+			lost_center: false,
+		}
+	}
+
+	pub fn mobs_seen(&mut self, mobs: Vec<(Uuid, Vec3)>) {
+		for (uuid, pos) in mobs {
+			self.mobs.seen(uuid, Some(pos));
+		}
+	}
+
+	/// where the index-th of count guards stands, spread evenly on a circle halfway to the edge
+	pub fn post(&self, center: Vec3, index: usize, count: usize) -> Vec3 {
+		let angle = std::f64::consts::TAU * index as f64 / count.max(1) as f64;
+		let distance = self.radius / 2.0;
+		Vec3::new(
+			center.x + angle.cos() * distance,
+			center.y,
+			center.z + angle.sin() * distance,
+		)
+	}
+}
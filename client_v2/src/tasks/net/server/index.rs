@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use azalea::{BlockPos, registry::Item};
+
+use super::world::{World, is_container};
+
+/// what's in every container a bot has opened, as of when it was last opened
+#[derive(Debug, Default)]
+pub struct StorageIndex {
+	/// pos -> item -> count
+	containers: HashMap<BlockPos, HashMap<Item, i32>>,
+}
+impl StorageIndex {
+	pub fn update(&mut self, pos: BlockPos, items: Vec<(Item, i32)>) {
+		self.containers.insert(pos, items.into_iter().collect());
+	}
+
+	/// containers with the item in them and how many, most first \
+	/// the ones that aren't there anymore are forgotten
+	pub fn find(&mut self, item: Item, world: &World) -> Vec<(BlockPos, i32)> {
+		self.containers
+			.retain(|pos, _| world.get(*pos).map(is_container).unwrap_or(true));
+
+		let mut found = self
+			.containers
+			.iter()
+			.filter_map(|(pos, items)| Some((*pos, *items.get(&item)?)))
+			.filter(|(_, count)| *count > 0)
+			.collect::<Vec<_>>();
+		found.sort_by_key(|(_, count)| -count);
+		found
+	}
+
+	/// the container closest to `near` with the item in it
+	pub fn closest(
+		&mut self,
+		item: Item,
+		near: BlockPos,
+		world: &World,
+	) -> Option<(BlockPos, i32)> {
+		let distance = |pos: &BlockPos| {
+			(pos.x - near.x).abs() + (pos.y - near.y).abs() + (pos.z - near.z).abs()
+		};
+		self.find(item, world)
+			.into_iter()
+			.min_by_key(|(pos, _)| distance(pos))
+	}
+
+	/// someone's on their way to take some out, so nobody else is sent for the same items
+	pub fn taken(&mut self, pos: BlockPos, item: Item, count: i32) {
+		if let Some(items) = self.containers.get_mut(&pos) {
+			if let Some(left) = items.get_mut(&item) {
+				*left = (*left - count).max(0);
+			}
+		}
+	}
+
+	pub fn total(&self, item: Item) -> i32 {
+		self.containers
+			.values()
+			.filter_map(|items| items.get(&item))
+			.sum()
+	}
+}
@@ -0,0 +1,310 @@
+// turning the bigger chat commands into jobs
+
+use std::{
+	collections::{BTreeSet, HashSet},
+	str::FromStr,
+};
+
+use azalea::{BlockPos, registry::Block};
+
+use super::{graph::Job, world::World};
+use crate::{
+	schematic::{Schematic, block_id},
+	tasks::{
+		Task,
+		farm::is_mature,
+		lumber::{is_leaves, is_log, sapling_for},
+		net::cuboid,
+	},
+};
+
+/// the tasks that turn some positions into the blocks we want there
+#[derive(Debug, Default)]
+pub struct Plan {
+	pub job: Job,
+	pub mines: usize,
+	pub places: usize,
+	/// positions we haven't seen yet -> the block that should end up there
+	pub unknown: Vec<(BlockPos, Block)>,
+}
+
+/// mines whatever's in the way and places the blocks, skipping the ones that already match \
+/// positions we haven't seen yet are mined too just in case \
+/// (those get skipped once a scan comes back saying they're fine as they are, see `Plan::unknown`)
+pub fn set_blocks(blocks: impl IntoIterator<Item = (BlockPos, Block)>, world: &World) -> Plan {
+	let mut mine = Vec::new();
+	let mut place = Vec::new();
+	let mut unknown = Vec::new();
+	for (pos, block) in blocks {
+		let target_air = matches!(block, Block::Air | Block::CaveAir | Block::VoidAir);
+
+		match world.get(pos) {
+			Some(state) if Block::from(state) == block => continue,
+			Some(state) if target_air => {
+				if super::world::minable(state) {
+					mine.push(pos);
+				}
+			}
+			Some(state) => {
+				if !pathfind::replaceable(state) {
+					mine.push(pos);
+				}
+				place.push((pos, block));
+			}
+			None => {
+				unknown.push((pos, block));
+				mine.push(pos);
+				if !target_air {
+					place.push((pos, block));
+				}
+			}
+		}
+	}
+
+	let mut plan = Plan {
+		mines: mine.len(),
+		places: place.len(),
+		unknown,
+		..Default::default()
+	};
+	let mines = plan.job.mine_all(mine);
+	plan.job.place_all(place, &mines);
+	plan
+}
+
+/// like /fill
+pub fn fill(from: BlockPos, to: BlockPos, block: Block, world: &World) -> Plan {
+	set_blocks(cuboid(from, to).map(|pos| (pos, block)), world)
+}
+
+/// like /fill ... replace, only touches the positions we know are `old` \
+/// so the area should be scanned first, see World::is_known
+pub fn replace(from: BlockPos, to: BlockPos, old: Block, new: Block, world: &World) -> Plan {
+	let blocks = cuboid(from, to)
+		.filter(|pos| {
+			world
+				.get(*pos)
+				.map(|state| Block::from(state) == old)
+				.unwrap_or(false)
+		})
+		.map(|pos| (pos, new));
+	set_blocks(blocks, world)
+}
+
+#[derive(Debug, Default)]
+pub struct Build {
+	pub plan: Plan,
+	/// block ids in the schematic azalea doesn't know about, those are left out
+	pub unknown: BTreeSet<String>,
+	/// the corners of the area the schematic ends up in
+	pub from: BlockPos,
+	pub to: BlockPos,
+}
+
+/// builds the schematic with its lowest corner at `origin`, turned clockwise `rotation` times
+pub fn build(schematic: &Schematic, origin: BlockPos, rotation: u8, world: &World) -> Build {
+	let mut build = Build::default();
+
+	let schematic = schematic.rotated(rotation);
+	let at = |[x, y, z]: [i32; 3]| BlockPos::new(origin.x + x, origin.y + y, origin.z + z);
+	build.from = origin;
+	build.to = at(schematic.size.map(|a| (a - 1).max(0)));
+
+	let mut blocks = Vec::new();
+	for (offset, state) in schematic.blocks.iter() {
+		let id = block_id(state);
+		match Block::from_str(id) {
+			Ok(block) => blocks.push((at(*offset), block)),
+			Err(_) => {
+				build.unknown.insert(id.to_owned());
+			}
+		}
+	}
+	build.plan = set_blocks(blocks, world);
+	build
+}
+
+/// trees bigger than this are probably someone's house
+const MAX_TREE: usize = 300;
+
+/// a Fell task for every tree we know of around `center` \
+/// a tree being logs connected to each other, with leaves on them and standing on the ground
+pub fn lumber(center: BlockPos, radius: i32, world: &World) -> Vec<Task> {
+	let block_at = |pos: BlockPos| world.get(pos).map(Block::from);
+
+	let from = BlockPos::new(center.x - radius, center.y - 16, center.z - radius);
+	let to = BlockPos::new(center.x + radius, center.y + 32, center.z + radius);
+
+	let mut seen = HashSet::new();
+	let mut tasks = Vec::new();
+	for start in cuboid(from, to) {
+		if seen.contains(&start) || !block_at(start).map(is_log).unwrap_or(false) {
+			continue;
+		}
+
+		// everything touching, diagonals included since branches go sideways
+		let mut logs = Vec::new();
+		let mut stack = vec![start];
+		seen.insert(start);
+		while let Some(pos) = stack.pop() {
+			logs.push(pos);
+			for neighbor in cuboid(pos.down(1).north(1).west(1), pos.up(1).south(1).east(1)) {
+				if block_at(neighbor).map(is_log).unwrap_or(false) && seen.insert(neighbor) {
+					stack.push(neighbor);
+				}
+			}
+		}
+		if logs.len() > MAX_TREE {
+			continue;
+		}
+
+		let has_leaves = logs.iter().any(|pos| {
+			cuboid(pos.down(1).north(1).west(1), pos.up(1).south(1).east(1))
+				.any(|neighbor| block_at(neighbor).map(is_leaves).unwrap_or(false))
+		});
+		// the bottom of every column that's standing on something that isn't a log
+		let replant = logs
+			.iter()
+			.filter(|pos| {
+				block_at(pos.down(1))
+					.map(|below| !is_log(below) && below != Block::Air)
+					.unwrap_or(false)
+			})
+			.filter_map(|pos| Some((*pos, sapling_for(block_at(*pos)?)?)))
+			.collect::<Vec<_>>();
+		if !has_leaves || replant.is_empty() {
+			continue;
+		}
+
+		tasks.push(Task::Fell { logs, replant });
+	}
+	tasks
+}
+
+/// a Harvest task for every fully grown crop in the area, except the ones in `skip`
+pub fn harvest(from: BlockPos, to: BlockPos, skip: &HashSet<BlockPos>, world: &World) -> Vec<Task> {
+	cuboid(from, to)
+		.filter(|pos| !skip.contains(pos))
+		.filter_map(|pos| {
+			let state = world.get(pos)?;
+			is_mature(state).then(|| Task::Harvest {
+				pos,
+				crop: Block::from(state),
+			})
+		})
+		.collect()
+}
+
+/// how far apart branches are, two blocks of stone between them means every block gets seen
+const BRANCH_SPACING: i32 = 3;
+
+/// a 1x2 corridor going `dir` from `start` with 1x2 branches off both sides of it \
+/// every branch only waits for the corridor to get to it, so different bots can take different branches
+pub fn stripmine(start: BlockPos, dir: (i32, i32), length: i32, branch: i32) -> Job {
+	let mut job = Job::new();
+
+	// mines the 2 high column at pos after `after`, returns the index of the lower block
+	let column = |job: &mut Job, pos: BlockPos, after: Option<usize>| {
+		let top = job.push_after(Task::Mine(pos.up(1)), after);
+		job.push_after(Task::Mine(pos), [top])
+	};
+
+	let mut previous = None;
+	for i in 0..length {
+		let pos = BlockPos::new(start.x + dir.0 * i, start.y, start.z + dir.1 * i);
+		let corridor = column(&mut job, pos, previous);
+		previous = Some(corridor);
+
+		if i % BRANCH_SPACING != BRANCH_SPACING - 1 {
+			continue;
+		}
+		// both sides, perpendicular to the corridor
+		for side in [(-dir.1, dir.0), (dir.1, -dir.0)] {
+			let mut previous = Some(corridor);
+			for j in 1..=branch {
+				let pos = BlockPos::new(pos.x + side.0 * j, pos.y, pos.z + side.1 * j);
+				previous = Some(column(&mut job, pos, previous));
+			}
+		}
+	}
+	job
+}
+
+/// how many slices of a tunnel or staircase go between torches
+const TORCH_INTERVAL: usize = 8;
+
+/// digs the slices one after another, each slice being (the block you'd stand in, how tall) columns \
+/// a slice is only started once the one before it is walkable, and gets a torch every few slices
+fn dig_slices(slices: Vec<Vec<(BlockPos, i32)>>) -> Job {
+	let mut job = Job::new();
+
+	let mut previous = Vec::new();
+	for (i, slice) in slices.into_iter().enumerate() {
+		let mut bottoms = Vec::new();
+		for (bottom, height) in slice.iter().copied() {
+			let mut above = None;
+			for y in (0..height).rev() {
+				let after = previous.iter().copied().chain(above);
+				above = Some(job.push_after(Task::Mine(bottom.up(y)), after));
+			}
+			bottoms.extend(above);
+		}
+
+		if i % TORCH_INTERVAL == TORCH_INTERVAL - 1 {
+			if let Some((pos, _)) = slice.first() {
+				job.push_after(
+					Task::Place {
+						pos: *pos,
+						block: Block::Torch,
+					},
+					bottoms.iter().copied(),
+				);
+			}
+		}
+		previous = bottoms;
+	}
+	job
+}
+
+/// a w wide h tall tunnel going `dir` from `start`, `start` being its bottom left corner
+pub fn tunnel(start: BlockPos, dir: (i32, i32), length: i32, width: i32, height: i32) -> Job {
+	// to the right of the direction we're going
+	let right = (-dir.1, dir.0);
+	let slices = (0..length)
+		.map(|i| {
+			(0..width)
+				.map(|j| {
+					let pos = BlockPos::new(
+						start.x + dir.0 * i + right.0 * j,
+						start.y,
+						start.z + dir.1 * i + right.1 * j,
+					);
+					(pos, height)
+				})
+				.collect()
+		})
+		.collect();
+	dig_slices(slices)
+}
+
+/// a staircase going `dir` from `start`, one block up or down every block until it gets to `to_y` \
+/// `width` blocks wide to the right, every step `height` tall so there's room to walk down it
+pub fn stairs(start: BlockPos, dir: (i32, i32), to_y: i32, width: i32, height: i32) -> Job {
+	let right = (-dir.1, dir.0);
+	let step = (to_y - start.y).signum();
+	let slices = (0..=(to_y - start.y).abs())
+		.map(|i| {
+			(0..width)
+				.map(|j| {
+					let pos = BlockPos::new(
+						start.x + dir.0 * i + right.0 * j,
+						start.y + step * i,
+						start.z + dir.1 * i + right.1 * j,
+					);
+					(pos, height)
+				})
+				.collect()
+		})
+		.collect();
+	dig_slices(slices)
+}
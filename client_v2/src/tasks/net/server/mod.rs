@@ -1,22 +1,40 @@
 use std::{
-	collections::VecDeque,
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
 	iter::Enumerate,
 	sync::Arc,
 	time::{Duration, Instant},
 };
 
 use anyhow::anyhow;
-use azalea::{BlockPos, Vec3, pathfinder::goals::RadiusGoal};
-use tokio::{net::TcpListener, sync::Mutex};
+use azalea::{BlockPos, Vec3, pathfinder::goals::RadiusGoal, registry::Item};
+use tokio::{
+	net::{TcpListener, tcp::OwnedWriteHalf},
+	sync::Mutex,
+};
 
+pub mod agro;
+pub mod graph;
+pub mod guard;
+pub mod index;
+pub mod jobs;
 pub mod per_inst;
+pub mod regions;
+pub mod smelt;
+pub mod stash;
+pub mod targets;
+pub mod world;
 
 use crate::{
 	namegen::NameGen,
+	schematic::Schematic,
+	tasks::combat::CombatStyle,
+	tasks::craft,
+	tasks::survival::Vitals,
 	tasks::{
 		Task,
 		net::{
 			ClientboundHelloPacket, ClientboundPacket, ServerboundHelloPacket, ServerboundPacket,
+			TaskResult,
 		},
 	},
 };
@@ -25,6 +43,17 @@ use honeypack::{PacketRead, PacketWrite};
 
 use super::PosReport;
 
+/// how often farms are checked for crops that finished growing
+const FARM_INTERVAL: Duration = Duration::from_secs(30);
+/// how many of an item a bot takes out of a container at once
+const FETCH_COUNT: i32 = 64;
+/// how long before an instance is told to craft the same thing again
+const CRAFT_COOLDOWN: Duration = Duration::from_secs(60);
+/// dropped items disappear after 5 minutes
+const ITEM_DESPAWN: Duration = Duration::from_secs(5 * 60);
+/// how long a replace waits for its area to be scanned before giving up
+const SCAN_TIMEOUT: Duration = Duration::from_secs(60);
+
 #[derive(Debug)]
 struct ServerData {
 	namegen: Enumerate<NameGen<'static>>,
@@ -34,12 +63,793 @@ struct ServerData {
 
 	task_queue: VecDeque<Task>,
 	per_inst: per_inst::PerInstanceTasks,
+	graph: graph::TaskGraph,
+	regions: regions::Regions,
+	/// inst_id -> the last time that instance requested a task
+	active: HashMap<i32, Instant>,
+	world: world::World,
+	/// areas being demolished, checked against the world once their tasks run out
+	demolishing: Vec<Demolish>,
+	/// replace jobs waiting for their area to be scanned
+	replacing: Vec<Replace>,
+	/// positions that weren't known when their job was scheduled -> the block that should end up there \
+	/// their tasks are skipped if it turns out it was there all along
+	targets: HashMap<BlockPos, azalea::registry::Block>,
+	/// inst_id -> what it had on it after its last task
+	inventories: HashMap<i32, HashMap<Item, i32>>,
+	/// inst_id -> how many empty slots it had after its last task
+	free_slots: HashMap<i32, i32>,
+	stash: stash::StashPolicy,
+	/// what's in the containers the bots have opened
+	index: index::StorageIndex,
+	smelting: smelt::Smelting,
+	/// (inst_id, item) -> when it was last told to craft it, so it doesn't keep trying something that doesn't work
+	craft_attempts: HashMap<(i32, Item), Instant>,
+	/// inst_id -> tasks only that instance can do, like handing over what it has on it
+	directed: HashMap<i32, VecDeque<Task>>,
+	/// inst_id -> the directed task it's on right now, so we know what its TaskDone is about
+	handed_out: HashMap<i32, Task>,
+	/// deliveries to other bots, checked against the recipient's next inventory report
+	receipts: Vec<Receipt>,
+	/// inst_id -> the bot's username
+	usernames: HashMap<i32, String>,
+	/// where bots died and when, waiting for someone to pick up what they dropped
+	deaths: VecDeque<(Vec3, Instant)>,
+	/// whether anyone goes to pick up a dead bot's items, see `gang recover`
+	recover_items: bool,
+	/// inst_id -> its health and hunger as of the last report
+	vitals: HashMap<i32, Vitals>,
+	gathering: Vec<Gather>,
+	farms: Vec<Farm>,
+	stripmines: Vec<Stripmine>,
+	/// tunnels and staircases, watched for lava and water leaking in
+	digs: Vec<Dig>,
+	guard: Option<guard::Guard>,
+	/// whatever attacked one of the bots
+	enemies: targets::Targets,
+	agro: agro::AgroPolicy,
+	/// every bot's username, they're never fought back against
+	bots: HashSet<String>,
+	/// how the bots fight, the default and then per username
+	combat: CombatStyle,
+	combat_per_bot: HashMap<String, CombatStyle>,
+	/// chat messages waiting for a bot to say them
+	say: VecDeque<String>,
+}
+impl ServerData {
+	fn is_active(&self, inst_id: i32) -> bool {
+		self.active
+			.get(&inst_id)
+			.map(|last| last.elapsed() < Duration::from_secs(10))
+			.unwrap_or(false)
+	}
+	/// the number of instances that requested a task recently, the ones a job would be split between
+	fn active_instances(&self) -> usize {
+		self.active.keys().filter(|id| self.is_active(**id)).count()
+	}
+	/// adds the job to the graph and splits it between the instances
+	fn schedule(&mut self, job: graph::Job) {
+		let ids = self.graph.add(job);
+		let tasks = ids
+			.into_iter()
+			.map(|id| (id, self.graph.task(id).and_then(|task| task.pos())))
+			.collect::<Vec<_>>();
+
+		let slices = self.active_instances();
+		self.regions.add(tasks, slices);
+	}
+	/// schedules the plan's job, remembering what its unknown positions should end up as
+	fn schedule_plan(&mut self, plan: jobs::Plan) {
+		self.targets.extend(plan.unknown);
+		self.schedule(plan.job);
+	}
+	/// a trip to the closest container with the block the task places if the instance has none of it \
+	/// or crafting it if no container has it
+	fn fetch_for(&mut self, inst_id: i32, task: &Task) -> Option<Task> {
+		let Task::Place { pos, block } = task else {
+			return None;
+		};
+		let item = pathfind::item_for(*block).ok()?;
+		let have = self.inventories.get(&inst_id)?.get(&item).copied();
+		if have.unwrap_or_default() > 0 {
+			return None;
+		}
+
+		let Some((container, count)) = self.index.closest(item, *pos, &self.world) else {
+			return self.craft_for(inst_id, item, FETCH_COUNT);
+		};
+		let count = count.min(FETCH_COUNT);
+		self.index.taken(container, item, count);
+		Some(Task::Fetch {
+			container,
+			item,
+			count,
+		})
+	}
+	/// empties the furnaces that should be done by now
+	fn schedule_collects(&mut self) {
+		let due = self.smelting.due();
+		if due.is_empty() {
+			return;
+		}
+		let mut job = graph::Job::new();
+		for furnace in due {
+			job.push(Task::CollectFurnace(furnace));
+		}
+		self.schedule(job);
+	}
+	/// crafting something the instance needs out of what it has on it
+	fn craft_for(&mut self, inst_id: i32, item: Item, count: i32) -> Option<Task> {
+		let items = self.inventories.get(&inst_id)?;
+		if !craft::craftable(item, items) {
+			return None;
+		}
+		if let Some(at) = self.craft_attempts.get(&(inst_id, item)) {
+			if at.elapsed() < CRAFT_COOLDOWN {
+				return None;
+			}
+		}
+		self.craft_attempts.insert((inst_id, item), Instant::now());
+		Some(Task::Craft { item, count })
+	}
+	/// crafts what's missing from the restock list before going to the chests for it
+	fn restock_craft_for(&mut self, inst_id: i32) -> Option<Task> {
+		let items = self.inventories.get(&inst_id)?;
+		let missing = self
+			.stash
+			.restock
+			.iter()
+			.filter_map(|(item, want)| {
+				let have = items.get(item).copied().unwrap_or_default();
+				(have < *want).then_some((*item, want - have))
+			})
+			.collect::<Vec<_>>();
+		missing
+			.into_iter()
+			.find_map(|(item, count)| self.craft_for(inst_id, item, count))
+	}
+	/// splits the hand-off between the bots that have the item on them, the rest is fetched from storage \
+	/// returns how many couldn't be found anywhere
+	fn give(&mut self, to: &str, item: Item, count: i32) -> i32 {
+		let mut holders = self
+			.inventories
+			.iter()
+			.filter_map(|(inst_id, items)| Some((*inst_id, *items.get(&item)?)))
+			.filter(|(_, have)| *have > 0)
+			.collect::<Vec<_>>();
+		holders.sort_by_key(|(_, have)| -have);
+
+		let mut left = count;
+		for (inst_id, have) in holders {
+			if left <= 0 {
+				break;
+			}
+			let count = have.min(left);
+			self.directed
+				.entry(inst_id)
+				.or_default()
+				.push_back(Task::Deliver {
+					to: to.to_owned(),
+					item,
+					count,
+				});
+			left -= count;
+		}
+
+		// whoever has the most room goes and gets the rest
+		while left > 0 {
+			let Some((container, have)) = self.index.find(item, &self.world).into_iter().next()
+			else {
+				break;
+			};
+			let Some(inst_id) = self
+				.free_slots
+				.iter()
+				.max_by_key(|(_, free)| **free)
+				.map(|(inst_id, _)| *inst_id)
+			else {
+				break;
+			};
+
+			let count = have.min(left);
+			self.index.taken(container, item, count);
+			let tasks = self.directed.entry(inst_id).or_default();
+			tasks.push_back(Task::Fetch {
+				container,
+				item,
+				count,
+			});
+			tasks.push_back(Task::Deliver {
+				to: to.to_owned(),
+				item,
+				count,
+			});
+			left -= count;
+		}
+		left
+	}
+	/// tells the owner how a hand-off went, a fetch that fails takes the delivery after it along \
+	/// and a furnace that couldn't be loaded isn't waited on
+	fn directed_done(&mut self, inst_id: i32, task: Task, result: &TaskResult) {
+		let err = match result {
+			TaskResult::Done => None,
+			TaskResult::Failed(err) | TaskResult::Interrupted(err) | TaskResult::NoTool(err) => {
+				Some(err)
+			}
+		};
+		match (task, err) {
+			(Task::Deliver { to, item, count }, None) => {
+				let recipient = self
+					.usernames
+					.iter()
+					.find(|(_, name)| **name == to)
+					.map(|(inst_id, _)| *inst_id);
+				match recipient {
+					// the items being gone off the ground doesn't mean the bot picked them up
+					Some(to_inst) => {
+						let have = self
+							.inventories
+							.get(&to_inst)
+							.and_then(|items| items.get(&item))
+							.copied()
+							.unwrap_or(0);
+						self.receipts.push(Receipt {
+							inst_id: to_inst,
+							to,
+							item,
+							count,
+							expect: have + count,
+						});
+					}
+					None => self
+						.say
+						.push_back(format!("gave {to} {count} {}", item_name(item))),
+				}
+			}
+			(Task::Deliver { to, item, count }, Some(err)) => {
+				self.say.push_back(format!(
+					"couldn't give {to} {count} {}: {err}",
+					item_name(item)
+				));
+			}
+			(Task::Fetch { item, .. }, Some(err)) => {
+				// whatever it could've fetched is all it'd have to hand over
+				let Some(tasks) = self.directed.get_mut(&inst_id) else {
+					return;
+				};
+				let Some(i) = tasks
+					.iter()
+					.position(|task| matches!(task, Task::Deliver { item: i, .. } if *i == item))
+				else {
+					return;
+				};
+				if let Some(Task::Deliver { to, count, .. }) = tasks.remove(i) {
+					self.say.push_back(format!(
+						"couldn't get {count} {} out of storage for {to}: {err}",
+						item_name(item)
+					));
+				}
+			}
+			(Task::LoadFurnace { furnace, .. }, Some(_)) => {
+				self.smelting.not_loaded(furnace);
+			}
+			_ => {}
+		}
+	}
+	/// checks the deliveries to this bot against what it has now
+	fn check_receipts(&mut self, inst_id: i32, items: &HashMap<Item, i32>) {
+		let (done, waiting) = std::mem::take(&mut self.receipts)
+			.into_iter()
+			.partition::<Vec<_>, _>(|receipt| receipt.inst_id == inst_id);
+		self.receipts = waiting;
+		for receipt in done {
+			let Receipt {
+				to,
+				item,
+				count,
+				expect,
+				..
+			} = receipt;
+			let have = items.get(&item).copied().unwrap_or(0);
+			let message = if have >= expect {
+				format!("gave {to} {count} {}", item_name(item))
+			} else {
+				format!(
+					"threw {to} {count} {} but it only picked up {}",
+					item_name(item),
+					(count - (expect - have)).max(0)
+				)
+			};
+			self.say.push_back(message);
+		}
+	}
+	/// the instance won't finish its task, it goes back up for grabs
+	fn release_task(&mut self, inst_id: i32) {
+		if let Some(id) = self.graph.release(inst_id) {
+			self.put_back(id, inst_id);
+		}
+	}
+	/// puts a task the instance had back in a region, so it's handed out again
+	fn put_back(&mut self, id: graph::NodeId, inst_id: i32) {
+		let pos = self.graph.task(id).and_then(|task| task.pos());
+		self.regions.put_back(id, pos, inst_id);
+	}
+	/// the next task from this instance's region, skipping the ones we know are already done
+	fn region_task_for(&mut self, inst_id: i32) -> Option<Task> {
+		loop {
+			match self.regions.task_for(inst_id, &self.graph) {
+				Some(id) => {
+					let done = match self.graph.task(id).cloned() {
+						Some(task) => self.already_done(&task),
+						None => true,
+					};
+					if done {
+						if let Some(task) = self.graph.complete(id) {
+							self.finished(&task);
+						}
+						continue;
+					}
+					let task = self.graph.lease(id, inst_id)?;
+					if let Some(fetch) = self.fetch_for(inst_id, &task) {
+						// it'll get the task again once it has what it needs
+						self.release_task(inst_id);
+						return Some(fetch);
+					}
+					return Some(task);
+				}
+				None if self.graph.is_empty() && self.recheck_demolished() => continue,
+				None => return None,
+			}
+		}
+	}
+	/// whether we know the task has nothing left to do \
+	/// including positions that were unknown when scheduled and turned out to be what we wanted
+	fn already_done(&mut self, task: &Task) -> bool {
+		if self.world.already_done(task) {
+			return true;
+		}
+		let (Task::Mine(pos) | Task::Place { pos, .. }) = task else {
+			return false;
+		};
+		let Some(target) = self.targets.get(pos).copied() else {
+			return false;
+		};
+		let Some(state) = self.world.get(*pos) else {
+			return false;
+		};
+		// we know what's there now, so the tasks go ahead as usual unless it's already right
+		self.targets.remove(pos);
+		azalea::registry::Block::from(state) == target
+	}
+	/// bookkeeping for tasks that won't be handed out again, whether they were done or given up on
+	fn finished(&mut self, task: &Task) {
+		if let Task::CollectFurnace(furnace) = task {
+			self.smelting.collected(*furnace);
+			return;
+		}
+		let Task::Mine(pos) = task else {
+			return;
+		};
+		let pos = *pos;
+
+		let mut ores = Vec::new();
+		for stripmine in self.stripmines.iter_mut() {
+			if !stripmine.remaining.remove(&pos) {
+				continue;
+			}
+			// ores that were behind the block we just mined, and the rest of the vein once we mine those
+			for neighbor in [
+				pos.up(1),
+				pos.down(1),
+				pos.north(1),
+				pos.south(1),
+				pos.east(1),
+				pos.west(1),
+			] {
+				let Some(block) = self.world.get(neighbor).map(azalea::registry::Block::from)
+				else {
+					continue;
+				};
+				if world::is_ore(block) && !stripmine.remaining.contains(&neighbor) {
+					if stripmine
+						.ores
+						.values()
+						.any(|found| found.contains(&neighbor))
+					{
+						continue;
+					}
+					stripmine
+						.ores
+						.entry(block_name(block))
+						.or_default()
+						.push(neighbor);
+					stripmine.remaining.insert(neighbor);
+					ores.push(neighbor);
+				}
+			}
+		}
+		if !ores.is_empty() {
+			self.schedule(graph::Job::mine(ores));
+		}
+
+		let mut plugs = graph::Job::new();
+		for dig in self.digs.iter_mut() {
+			if !dig.remaining.remove(&pos) {
+				continue;
+			}
+			// whatever's leaking in from outside gets a block put in its way
+			for neighbor in [
+				pos.up(1),
+				pos.down(1),
+				pos.north(1),
+				pos.south(1),
+				pos.east(1),
+				pos.west(1),
+			] {
+				let liquid = self
+					.world
+					.get(neighbor)
+					.map(world::is_liquid)
+					.unwrap_or(false);
+				if liquid && !dig.area.contains(&neighbor) && dig.plugged.insert(neighbor) {
+					plugs.push(Task::Plug(neighbor));
+				}
+			}
+		}
+		self.digs.retain(|dig| !dig.remaining.is_empty());
+		if !plugs.is_empty() {
+			self.schedule(plugs);
+		}
+
+		for stripmine in std::mem::take(&mut self.stripmines) {
+			if stripmine.remaining.is_empty() {
+				self.report_stripmine(&stripmine);
+			} else {
+				self.stripmines.push(stripmine);
+			}
+		}
+	}
+	/// tells the owner what a finished stripmine found
+	fn report_stripmine(&mut self, stripmine: &Stripmine) {
+		if stripmine.ores.is_empty() {
+			self.say
+				.push_back("finished stripmining, didn't find any ores".to_owned());
+			return;
+		}
+
+		let counts = stripmine
+			.ores
+			.iter()
+			.map(|(block, found)| format!("{} {block}", found.len()))
+			.collect::<Vec<_>>();
+		self.say
+			.push_back(format!("finished stripmining, found {}", counts.join(", ")));
+
+		for (block, found) in stripmine.ores.iter() {
+			let mut message = format!("{block}:");
+			for pos in found {
+				let next = format!(" {} {} {}", pos.x, pos.y, pos.z);
+				// chat messages can't be longer than 256 characters
+				if message.len() + next.len() > 240 {
+					message += " ...";
+					break;
+				}
+				message += &next;
+			}
+			self.say.push_back(message);
+		}
+	}
+	/// someone goes to pick up whatever a bot dropped when it died, unless it already despawned
+	fn pick_up_task_for(&mut self) -> Option<Task> {
+		self.deaths.retain(|(_, at)| at.elapsed() < ITEM_DESPAWN);
+		let (pos, _) = self.deaths.pop_front()?;
+		Some(Task::PickUp(pos))
+	}
+
+	fn stash_task_for(&mut self, inst_id: i32) -> Option<Task> {
+		let items = self.inventories.get(&inst_id)?;
+		let free_slots = self.free_slots.get(&inst_id).copied()?;
+		let needed = self.needed_by(inst_id);
+		self.stash
+			.task_for(inst_id, items, free_slots, needed, &self.world)
+	}
+	/// the blocks the instance's leased task and what's left of its region are going to place
+	fn needed_by(&self, inst_id: i32) -> HashSet<Item> {
+		self.regions
+			.owned_by(inst_id)
+			.chain(self.graph.leased_by(inst_id))
+			.filter_map(|id| match self.graph.task(id)? {
+				Task::Place { block, .. } => pathfind::item_for(*block).ok(),
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// hurt bots aren't sent to fight until they've healed
+	fn can_fight(&self, inst_id: i32) -> bool {
+		self.vitals
+			.get(&inst_id)
+			.map(|vitals| vitals.can_fight())
+			.unwrap_or(true)
+	}
+
+	/// where the guard's center is right now, if we know
+	fn guard_center(&self) -> Option<Vec3> {
+		match self.guard.as_ref()?.center {
+			guard::Center::Pos(pos) => Some(pos),
+			guard::Center::Owner => {
+				let (time, pos) = self.owner_pos;
+				(time.elapsed() < Duration::from_secs(30)).then_some(pos)
+			}
+		}
+	}
+	/// where the instance stands guard
+	fn guard_post(&self, inst_id: i32) -> Option<Vec3> {
+		let center = self.guard_center()?;
+		let mut guards = self
+			.active
+			.keys()
+			.filter(|id| self.is_active(**id))
+			.copied()
+			.collect::<Vec<_>>();
+		guards.sort();
+		let index = guards
+			.iter()
+			.position(|id| *id == inst_id)
+			.unwrap_or_default();
+		Some(self.guard.as_ref()?.post(center, index, guards.len()))
+	}
+	/// a hostile mob to go after if we're guarding and there's any
+	fn guard_task_for(&mut self, inst_id: i32) -> Option<Task> {
+		if !self.can_fight(inst_id) {
+			return None;
+		}
+		let post = self.guard_post(inst_id)?;
+		let uuid = self.guard.as_mut()?.mobs.target_for(inst_id, Some(post))?;
+		Some(Task::Attack(uuid))
+	}
+	/// a gather task if we still need more of something, announcing the ones that are done \
+	/// and giving up on the ones every instance that worked on them is stuck on
+	fn gather_task_for(&mut self, inst_id: i32) -> Option<Task> {
+		for gather in std::mem::take(&mut self.gathering) {
+			let workers = gather
+				.workers
+				.iter()
+				.filter(|id| self.is_active(**id))
+				.collect::<Vec<_>>();
+			let stuck = !workers.is_empty() && workers.iter().all(|id| gather.is_stuck(**id));
+			if gather.collected >= gather.target {
+				self.say
+					.push_back(format!("gathered {} {:?}", gather.collected, gather.item));
+			} else if stuck {
+				self.say.push_back(format!(
+					"gave up gathering {:?} at {}/{}, nobody can find any more",
+					gather.item, gather.collected, gather.target
+				));
+			} else {
+				self.gathering.push(gather);
+			}
+		}
+
+		let inventory = self.inventories.get(&inst_id);
+		let gather = self
+			.gathering
+			.iter_mut()
+			.find(|gather| !gather.is_stuck(inst_id))?;
+		let held = inventory
+			.and_then(|inventory| inventory.get(&gather.item))
+			.copied()
+			.unwrap_or(0);
+		gather.stuck.remove(&inst_id);
+		gather.workers.insert(inst_id);
+		gather.assigned.insert(inst_id, held);
+		Some(Task::Gather(gather.item))
+	}
+	/// schedules harvesting whatever grew since the last time
+	fn schedule_harvests(&mut self) {
+		let mut job = graph::Job::new();
+		for farm in self.farms.iter_mut() {
+			// anything that's been scheduled for this long either failed or got lost
+			farm.scheduled
+				.retain(|_, since| since.elapsed() < Duration::from_secs(300));
+			let skip = farm.scheduled.keys().copied().collect();
+
+			for task in jobs::harvest(farm.from, farm.to, &skip, &self.world) {
+				if let Some(pos) = task.pos() {
+					farm.scheduled.insert(pos, Instant::now());
+				}
+				job.push(task);
+			}
+		}
+		if !job.is_empty() {
+			self.schedule(job);
+		}
+	}
+	/// schedules the replace jobs whose areas we know everything about now
+	fn schedule_replaces(&mut self) {
+		for replace in std::mem::take(&mut self.replacing) {
+			if !self.world.is_known(replace.from, replace.to) {
+				if replace.since.elapsed() < SCAN_TIMEOUT {
+					self.replacing.push(replace);
+				} else {
+					let (from, to) = (replace.from, replace.to);
+					self.say.push_back(format!(
+						"gave up replacing {:?}, nobody could see all of {} {} {} to {} {} {}",
+						replace.old, from.x, from.y, from.z, to.x, to.y, to.z
+					));
+				}
+				continue;
+			}
+			let Replace {
+				from, to, old, new, ..
+			} = replace;
+			let plan = jobs::replace(from, to, old, new, &self.world);
+			self.say
+				.push_back(format!("replacing {} {old:?} with {new:?}", plan.places));
+			self.schedule_plan(plan);
+		}
+	}
+	/// queues whatever's still standing in the areas we've run out of tasks for \
+	/// returns whether anything was queued
+	fn recheck_demolished(&mut self) -> bool {
+		let mut queued = false;
+
+		for mut area in std::mem::take(&mut self.demolishing) {
+			let remaining = self.world.remaining(area.from, area.to);
+			let (from, to) = (area.from, area.to);
+
+			if remaining.is_empty() {
+				self.say.push_back(format!(
+					"finished demolishing {} {} {} to {} {} {}",
+					from.x, from.y, from.z, to.x, to.y, to.z
+				));
+			} else if area.rechecks >= 3 {
+				self.say.push_back(format!(
+					"gave up on {} blocks between {} {} {} and {} {} {}",
+					remaining.len(),
+					from.x,
+					from.y,
+					from.z,
+					to.x,
+					to.y,
+					to.z
+				));
+			} else {
+				area.rechecks += 1;
+				self.schedule(graph::Job::mine(remaining));
+				self.demolishing.push(area);
+				queued = true;
+			}
+		}
+		queued
+	}
+}
+
+#[derive(Clone, Debug)]
+struct Gather {
+	item: Item,
+	target: i32,
+	/// how many gather tasks brought in so far, stashing or crafting with them later doesn't matter
+	collected: i32,
+	/// instances working on a gather task for this right now -> how many they had when they got it \
+	/// counted once their inventory report after the task comes in
+	assigned: HashMap<i32, i32>,
+	/// every instance that worked on this at some point
+	workers: HashSet<i32>,
+	/// instances that couldn't find anything to gather, they're left alone for a while
+	stuck: HashMap<i32, Instant>,
+}
+impl Gather {
+	fn is_stuck(&self, inst_id: i32) -> bool {
+		self.stuck
+			.get(&inst_id)
+			.is_some_and(|since| since.elapsed() < Duration::from_secs(30))
+	}
+}
+
+/// a delivery to a bot, it's only done once the items show up in its inventory
+#[derive(Clone, Debug)]
+struct Receipt {
+	/// the bot getting the items
+	inst_id: i32,
+	to: String,
+	item: Item,
+	count: i32,
+	/// how many it should have once it picked them up
+	expect: i32,
+}
+
+#[derive(Clone, Debug)]
+struct Farm {
+	from: BlockPos,
+	to: BlockPos,
+	/// crops that have a harvest task already, so they don't get another one
+	scheduled: HashMap<BlockPos, Instant>,
+}
+
+#[derive(Clone, Debug)]
+struct Dig {
+	area: HashSet<BlockPos>,
+	/// mine tasks that aren't finished yet
+	remaining: HashSet<BlockPos>,
+	/// liquids we've already scheduled plugging up
+	plugged: HashSet<BlockPos>,
+}
+impl Dig {
+	fn new(job: &graph::Job) -> Self {
+		let area = (0..job.len())
+			.filter_map(|i| match job.task(i) {
+				Some(Task::Mine(pos)) => Some(*pos),
+				_ => None,
+			})
+			.collect::<HashSet<_>>();
+		Self {
+			remaining: area.clone(),
+			area,
+			plugged: HashSet::new(),
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+struct Stripmine {
+	/// mine tasks that aren't finished yet, ores get added as they're found
+	remaining: HashSet<BlockPos>,
+	/// block name -> where we found it
+	ores: BTreeMap<String, Vec<BlockPos>>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Replace {
+	from: BlockPos,
+	to: BlockPos,
+	old: azalea::registry::Block,
+	new: azalea::registry::Block,
+	since: Instant,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Demolish {
+	from: BlockPos,
+	to: BlockPos,
+	/// how many times we've found blocks left over after running out of tasks
+	rechecks: u8,
+}
+
+/// the write half of a client's connection, the read half is owned by the task handling its requests
+#[derive(Clone, Debug)]
+struct Connection {
+	inst_id: i32,
+	pid: u32,
+	username: String,
+	write: Arc<Mutex<OwnedWriteHalf>>,
+}
+
+/// sends the packet to one instance in every client process, busy or not \
+/// instances in a process share their world, so asking all of them would just get the same answer n times
+async fn send_per_process(clients: &Mutex<Vec<Connection>>, packet: &ClientboundPacket) {
+	let targets = {
+		let clients = clients.lock().await;
+		let mut pids = HashSet::new();
+		clients
+			.iter()
+			.filter(|client| pids.insert(client.pid))
+			.cloned()
+			.collect::<Vec<_>>()
+	};
+	for client in targets {
+		let mut write = client.write.lock().await;
+		if let Err(err) = write.write_as_packet(packet).await {
+			eprintln!("couldn't send {packet:?} to {}: {err}", client.inst_id);
+		}
+	}
 }
 
 /// functional baby
 pub async fn start_server(owner: String) -> anyhow::Result<()> {
 	let listener = TcpListener::bind(super::ADDR).await?;
 
+	let agro = agro::AgroPolicy::default();
 	let data = ServerData {
 		owner,
 		owner_pos: (Instant::now() - Duration::from_hours(1), Vec3::default()),
@@ -47,13 +857,45 @@ pub async fn start_server(owner: String) -> anyhow::Result<()> {
 		chat_hash_handled: Vec::new(),
 		task_queue: VecDeque::new(),
 		per_inst: per_inst::PerInstanceTasks::default(),
+		graph: graph::TaskGraph::default(),
+		regions: regions::Regions::default(),
+		active: HashMap::new(),
+		world: world::World::default(),
+		demolishing: Vec::new(),
+		replacing: Vec::new(),
+		targets: HashMap::new(),
+		inventories: HashMap::new(),
+		free_slots: HashMap::new(),
+		stash: stash::StashPolicy::default(),
+		index: index::StorageIndex::default(),
+		directed: HashMap::new(),
+		handed_out: HashMap::new(),
+		receipts: Vec::new(),
+		usernames: HashMap::new(),
+		craft_attempts: HashMap::new(),
+		smelting: smelt::Smelting::default(),
+		vitals: HashMap::new(),
+		deaths: VecDeque::new(),
+		recover_items: true,
+		gathering: Vec::new(),
+		farms: Vec::new(),
+		stripmines: Vec::new(),
+		digs: Vec::new(),
+		guard: None,
+		enemies: targets::Targets::new(agro.expiry, 4),
+		agro,
+		bots: HashSet::new(),
+		combat: CombatStyle::default(),
+		combat_per_bot: HashMap::new(),
+		say: VecDeque::new(),
 	};
 	let data = Arc::new(Mutex::new(data));
-	let clients: Vec<Arc<Mutex<tokio::net::TcpStream>>> = Vec::new();
+	let clients: Vec<Connection> = Vec::new();
 	let clients = Arc::new(Mutex::new(clients));
 
 	let handle_chat = {
 		let data = data.clone();
+		let clients = clients.clone();
 		async move |sender, content: String| {
 			if let Some(sender) = sender {
 				if data.lock().await.owner == sender {
@@ -64,63 +906,606 @@ pub async fn start_server(owner: String) -> anyhow::Result<()> {
 					match words.next() {
 						Some("gang") => match words.next() {
 							Some("demolish") => {
-								let from_x: i32 = words
+								let from = parse_block_pos(&mut words)?;
+								let to = parse_block_pos(&mut words)?;
+
+								{
+									let mut data = data.lock().await;
+									data.schedule(graph::Job::demolish(from, to));
+									data.demolishing.push(Demolish {
+										from,
+										to,
+										rechecks: 0,
+									});
+								}
+
+								// so we can skip whatever's already air
+								let scan = ClientboundPacket::Scan { from, to };
+								send_per_process(&clients, &scan).await;
+							}
+							Some("stop") => {
+								let mut data = data.lock().await;
+								data.task_queue.clear();
+								data.per_inst.clear();
+								data.directed.clear();
+								data.handed_out.clear();
+								data.receipts.clear();
+								data.smelting.clear();
+								data.graph.clear();
+								data.regions.clear();
+								data.demolishing.clear();
+								data.replacing.clear();
+								data.targets.clear();
+								data.gathering.clear();
+								data.farms.clear();
+								data.stripmines.clear();
+								data.digs.clear();
+								data.enemies.clear();
+								data.deaths.clear();
+							}
+							Some("storage") => {
+								if words.clone().next() == Some("off") {
+									data.lock().await.stash.storage = None;
+								} else {
+									let from = parse_block_pos(&mut words)?;
+									let to = parse_block_pos(&mut words)?;
+									let known = {
+										let mut data = data.lock().await;
+										data.stash.storage = Some((from, to));
+										data.world.is_known(from, to)
+									};
+
+									// the chests have to be found first
+									if !known {
+										let scan = ClientboundPacket::Scan { from, to };
+										send_per_process(&clients, &scan).await;
+									}
+								}
+							}
+							Some("furnaces") => {
+								if words.clone().next() == Some("off") {
+									data.lock().await.smelting.area = None;
+								} else {
+									let from = parse_block_pos(&mut words)?;
+									let to = parse_block_pos(&mut words)?;
+									let known = {
+										let mut data = data.lock().await;
+										data.smelting.area = Some((from, to));
+										data.world.is_known(from, to)
+									};
+
+									// the furnaces have to be found first
+									if !known {
+										let scan = ClientboundPacket::Scan { from, to };
+										send_per_process(&clients, &scan).await;
+									}
+								}
+							}
+							Some("smelt") => {
+								let item = parse_item(words.next())?;
+								let count: Option<i32> =
+									words.next().map(str::parse).transpose()?;
+
+								let mut data = data.lock().await;
+								let holders = data
+									.inventories
+									.iter()
+									.filter_map(|(inst_id, items)| {
+										Some((*inst_id, *items.get(&item)?))
+									})
+									.filter(|(_, have)| *have > 0)
+									.collect::<Vec<_>>();
+								let total: i32 = holders.iter().map(|(_, have)| have).sum();
+								let count = count.unwrap_or(total);
+
+								let ServerData {
+									smelting, world, ..
+								} = &mut *data;
+								let tasks = smelting.plan(item, count, holders, world);
+								if tasks.is_empty() {
+									data.say.push_back(format!(
+										"nobody has any {item:?} or there are no free furnaces that take it"
+									));
+								}
+								for (inst_id, task) in tasks {
+									data.directed.entry(inst_id).or_default().push_back(task);
+								}
+							}
+							Some("craft") => {
+								let item = parse_item(words.next())?;
+								let count: i32 = match words.next() {
+									Some(count) => count.parse()?,
+									None => 1,
+								};
+								if craft::recipe_for(item).is_none() {
+									return Err(anyhow!("don't know how to craft {item:?}"));
+								}
+								data.lock()
+									.await
+									.task_queue
+									.push_back(Task::Craft { item, count });
+							}
+							Some("give") => {
+								let to =
+									words.next().ok_or_else(|| anyhow!("expected a player"))?;
+								let item = parse_item(words.next())?;
+								let count: i32 = words
 									.next()
-									.ok_or_else(|| anyhow!("expected x coordinate"))?
+									.ok_or_else(|| anyhow!("expected a count"))?
 									.parse()?;
-								let from_y: i32 = words
+
+								let mut data = data.lock().await;
+								let missing = data.give(to, item, count);
+								if missing > 0 {
+									data.say.push_back(format!(
+										"only found {} of the {count} {}",
+										count - missing,
+										item_name(item)
+									));
+								}
+							}
+							Some("where") => {
+								let item = parse_item(words.next())?;
+								let name = item_name(item);
+
+								let mut data = data.lock().await;
+								let ServerData { index, world, .. } = &mut *data;
+								let found = index.find(item, world);
+								let reply = if found.is_empty() {
+									format!("there's no {name} in any container we've opened")
+								} else {
+									let places = found
+										.iter()
+										.take(3)
+										.map(|(pos, count)| {
+											format!("{count} at {} {} {}", pos.x, pos.y, pos.z)
+										})
+										.collect::<Vec<_>>()
+										.join(", ");
+									format!(
+										"{} {name} in {} containers: {places}",
+										index.total(item),
+										found.len()
+									)
+								};
+								data.say.push_back(reply);
+							}
+							Some("keep") => {
+								let action = words.next();
+								let item = parse_item(words.next())?;
+								let mut data = data.lock().await;
+								match action {
+									Some("add") => {
+										data.stash.keep.insert(item);
+									}
+									Some("remove") => {
+										data.stash.keep.remove(&item);
+									}
+									_ => return Err(anyhow!("expected add or remove")),
+								}
+							}
+							Some("restock") => {
+								let item = parse_item(words.next())?;
+								let count: i32 = words
 									.next()
-									.ok_or_else(|| anyhow!("expected y coordinate"))?
+									.ok_or_else(|| anyhow!("expected a count"))?
 									.parse()?;
-								let from_z: i32 = words
+								let mut data = data.lock().await;
+								if count > 0 {
+									data.stash.restock.insert(item, count);
+								} else {
+									data.stash.restock.remove(&item);
+								}
+							}
+							Some("recover") => {
+								let on = match words.next() {
+									Some("on") => true,
+									Some("off") => false,
+									_ => return Err(anyhow!("expected on or off")),
+								};
+								let mut data = data.lock().await;
+								data.recover_items = on;
+								if !on {
+									data.deaths.clear();
+								}
+							}
+							Some("peace") => {
+								let seconds: f32 = match words.next() {
+									Some(seconds) => seconds.parse()?,
+									None => 10.0,
+								};
+
+								let mut data = data.lock().await;
+								data.enemies.clear();
+								if let Some(guard) = data.guard.as_mut() {
+									guard.mobs.clear();
+								}
+								data.agro.peace(Duration::from_secs_f32(seconds));
+							}
+							Some("place") => {
+								let pos = parse_block_pos(&mut words)?;
+								let block = parse_block(words.next())?;
+
+								let mut job = graph::Job::new();
+								job.push(Task::Place { pos, block });
+								data.lock().await.schedule(job);
+							}
+							Some("build") => {
+								let file =
+									words.next().ok_or_else(|| anyhow!("expected a file"))?;
+								let origin = parse_block_pos(&mut words)?;
+								let rotation = match words.next() {
+									Some(degrees) => {
+										let degrees = degrees.parse::<u16>()?;
+										if degrees % 90 != 0 {
+											data.lock().await.say.push_back(format!(
+												"builds only turn in steps of 90 degrees, {degrees} isn't one"
+											));
+											return Ok(());
+										}
+										degrees / 90 % 4
+									}
+									None => 0,
+								};
+
+								// bare names are looked up in ./schematics
+								let path = std::path::Path::new(file);
+								let path = if tokio::fs::try_exists(path).await.unwrap_or(false) {
+									path.to_owned()
+								} else {
+									std::path::Path::new("schematics").join(file)
+								};
+								// parsing a big schematic takes a while
+								let loaded =
+									tokio::task::spawn_blocking(move || Schematic::load(path))
+										.await?;
+								let schematic = match loaded {
+									Ok(a) => a,
+									Err(err) => {
+										let mut data = data.lock().await;
+										data.say.push_back(format!("couldn't load {file}: {err}"));
+										return Ok(());
+									}
+								};
+
+								let (from, to) = {
+									let mut data = data.lock().await;
+									let build = jobs::build(
+										&schematic,
+										origin,
+										rotation as u8,
+										&data.world,
+									);
+
+									let mut reply = format!(
+										"building {file}: {} to mine, {} to place",
+										build.plan.mines, build.plan.places
+									);
+									if !build.unknown.is_empty() {
+										let unknown =
+											build.unknown.iter().cloned().collect::<Vec<_>>();
+										reply += &format!(", skipping {}", unknown.join(", "));
+									}
+									data.say.push_back(reply);
+
+									data.schedule_plan(build.plan);
+									(build.from, build.to)
+								};
+
+								// so we can skip whatever's already in place
+								let scan = ClientboundPacket::Scan { from, to };
+								send_per_process(&clients, &scan).await;
+							}
+							Some("fill") => {
+								let from = parse_block_pos(&mut words)?;
+								let to = parse_block_pos(&mut words)?;
+								let block = parse_block(words.next())?;
+
+								{
+									let mut data = data.lock().await;
+									let plan = jobs::fill(from, to, block, &data.world);
+									data.schedule_plan(plan);
+								}
+
+								let scan = ClientboundPacket::Scan { from, to };
+								send_per_process(&clients, &scan).await;
+							}
+							Some("replace") => {
+								let from = parse_block_pos(&mut words)?;
+								let to = parse_block_pos(&mut words)?;
+								let old = parse_block(words.next())?;
+								let new = parse_block(words.next())?;
+
+								let known = {
+									let mut data = data.lock().await;
+									data.replacing.push(Replace {
+										from,
+										to,
+										old,
+										new,
+										since: Instant::now(),
+									});
+									data.schedule_replaces();
+									data.world.is_known(from, to)
+								};
+
+								// we can't tell what to replace without knowing what's there
+								if !known {
+									let scan = ClientboundPacket::Scan { from, to };
+									send_per_process(&clients, &scan).await;
+								}
+							}
+							Some("gather") => {
+								let item = parse_item(words.next())?;
+								let target = words
 									.next()
-									.ok_or_else(|| anyhow!("expected z coordinate"))?
+									.ok_or_else(|| anyhow!("expected a count"))?
 									.parse()?;
 
-								let to_x: i32 = words
+								let mut data = data.lock().await;
+								data.gathering.push(Gather {
+									item,
+									target,
+									collected: 0,
+									assigned: HashMap::new(),
+									workers: HashSet::new(),
+									stuck: HashMap::new(),
+								});
+							}
+							Some("farm") => {
+								let from = parse_block_pos(&mut words)?;
+								let to = parse_block_pos(&mut words)?;
+
+								{
+									let mut data = data.lock().await;
+									data.farms.push(Farm {
+										from,
+										to,
+										scheduled: HashMap::new(),
+									});
+									data.schedule_harvests();
+								}
+
+								let scan = ClientboundPacket::Scan { from, to };
+								send_per_process(&clients, &scan).await;
+							}
+							Some("stripmine") => {
+								let dir = parse_direction(words.next())?;
+								let length = words
 									.next()
-									.ok_or_else(|| anyhow!("expected x coordinate"))?
+									.ok_or_else(|| anyhow!("expected a length"))?
 									.parse()?;
-								let to_y: i32 = words
+								let branch = match words.next() {
+									Some(branch) => branch.parse()?,
+									None => 16,
+								};
+
+								let mut data = data.lock().await;
+								let (time, pos) = data.owner_pos;
+								if time.elapsed() > Duration::from_secs(30) {
+									data.say.push_back("i don't know where you are".to_owned());
+									return Ok(());
+								}
+
+								// starting right in front of the owner, at their feet
+								let feet = pos.to_block_pos_floor();
+								let start = BlockPos::new(feet.x + dir.0, feet.y, feet.z + dir.1);
+								let job = jobs::stripmine(start, dir, length, branch);
+
+								let remaining = (0..job.len())
+									.filter_map(|i| job.task(i).and_then(Task::pos))
+									.collect();
+								data.stripmines.push(Stripmine {
+									remaining,
+									ores: BTreeMap::new(),
+								});
+								data.schedule(job);
+							}
+							Some(kind @ ("tunnel" | "stairs")) => {
+								let dir = parse_direction(words.next())?;
+								let number = words
 									.next()
-									.ok_or_else(|| anyhow!("expected y coordinate"))?
-									.parse()?;
-								let to_z: i32 = words
+									.ok_or_else(|| match kind {
+										"tunnel" => anyhow!("expected a length"),
+										_ => anyhow!("expected a y level to go to"),
+									})?
+									.parse::<i32>()?;
+								// stairs are 1 wide unless asked otherwise
+								let width = match words.next() {
+									Some(width) => width.parse()?,
+									None if kind == "tunnel" => 2,
+									None => 1,
+								};
+								let height = match words.next() {
+									Some(height) => height.parse()?,
+									None => 3,
+								};
+
+								let mut data = data.lock().await;
+								let (time, pos) = data.owner_pos;
+								if time.elapsed() > Duration::from_secs(30) {
+									data.say.push_back("i don't know where you are".to_owned());
+									return Ok(());
+								}
+								// starting right in front of the owner, at their feet
+								let feet = pos.to_block_pos_floor();
+								let start = BlockPos::new(feet.x + dir.0, feet.y, feet.z + dir.1);
+
+								let job = match kind {
+									"tunnel" => jobs::tunnel(start, dir, number, width, height),
+									_ => jobs::stairs(start, dir, number, width, height),
+								};
+								data.digs.push(Dig::new(&job));
+								data.schedule(job);
+							}
+							Some("guard") => {
+								let args = words.collect::<Vec<_>>();
+								let mut data = data.lock().await;
+
+								let guard = match args.as_slice() {
+									["off"] => None,
+									[] => Some(guard::Guard::new(guard::Center::Owner, 16.0)),
+									[radius] => Some(guard::Guard::new(
+										guard::Center::Owner,
+										radius.parse()?,
+									)),
+									[x, y, z, rest @ ..] => {
+										let center = Vec3::new(x.parse()?, y.parse()?, z.parse()?);
+										let radius = match rest {
+											[radius, ..] => radius.parse()?,
+											[] => 16.0,
+										};
+										Some(guard::Guard::new(guard::Center::Pos(center), radius))
+									}
+									_ => return Err(anyhow!("expected a radius or a position")),
+								};
+								data.guard = guard;
+							}
+							Some("combat") => {
+								let who = words
 									.next()
-									.ok_or_else(|| anyhow!("expected z coordinate"))?
-									.parse()?;
+									.ok_or_else(|| anyhow!("expected a bot's name or all"))?;
+								let key =
+									words.next().ok_or_else(|| anyhow!("expected a setting"))?;
+								let value =
+									words.next().ok_or_else(|| anyhow!("expected a value"))?;
 
-								let from = (from_x, from_y, from_z);
-								let to = (to_x, to_y, to_z);
-
-								let from_x = from.0.max(to.0);
-								let from_y = from.1.max(to.1);
-								let from_z = from.2.max(to.2);
-								let to_x = from.0.min(to.0);
-								let to_y = from.1.min(to.1);
-								let to_z = from.2.min(to.2);
-
-								let to_add = (to_y..from_y + 1)
-									.rev()
-									.map(move |y| {
-										(to_x..from_x + 1).map(move |x| {
-											(to_z..from_z + 1)
-												.map(move |z| Task::Mine(BlockPos { x, y, z }))
-										})
-									})
-									.flatten()
-									.flatten();
+								let packet = {
+									let mut data = data.lock().await;
+									if who == "all" {
+										data.combat.set(key, value)?;
+										data.combat_per_bot.clear();
+										ClientboundPacket::Combat {
+											username: None,
+											style: data.combat.clone(),
+										}
+									} else {
+										let mut style = data
+											.combat_per_bot
+											.get(who)
+											.cloned()
+											.unwrap_or_else(|| data.combat.clone());
+										style.set(key, value)?;
+										data.combat_per_bot.insert(who.to_owned(), style.clone());
+										ClientboundPacket::Combat {
+											username: Some(who.to_owned()),
+											style,
+										}
+									}
+								};
+								// only the bot's own process needs to know how it fights
+								let bot = clients
+									.lock()
+									.await
+									.iter()
+									.find(|client| client.username == who)
+									.cloned();
+								match bot {
+									Some(bot) => {
+										bot.write.lock().await.write_as_packet(&packet).await?
+									}
+									None if who == "all" => {
+										send_per_process(&clients, &packet).await
+									}
+									None => return Err(anyhow!("there's no bot called {who}")),
+								}
+							}
+							Some(list @ ("friend" | "foe")) => {
+								let action = words.next();
+								let name =
+									words.next().ok_or_else(|| anyhow!("expected a name"))?;
 
 								let mut data = data.lock().await;
-								let queue_taken = std::mem::take(&mut data.task_queue);
-								data.task_queue = queue_taken.into_iter().chain(to_add).collect();
+								let agro = &mut data.agro;
+								let (add_to, remove_from) = match list {
+									"friend" => (&mut agro.friends, &mut agro.foes),
+									_ => (&mut agro.foes, &mut agro.friends),
+								};
+								match action {
+									Some("add") => {
+										// can't be both
+										remove_from.remove(name);
+										add_to.insert(name.to_owned());
+									}
+									Some("remove") => {
+										add_to.remove(name);
+									}
+									_ => return Err(anyhow!("expected add or remove")),
+								}
+							}
+							Some("agro") => {
+								let mut data = data.lock().await;
+								match (words.next(), words.next()) {
+									(Some(key), Some(value)) => {
+										data.agro.set(key, value)?;
+										let expiry = data.agro.expiry;
+										data.enemies.set_forget_after(expiry);
+									}
+									_ => {
+										let reply = data.agro.describe();
+										data.say.push_back(reply);
+									}
+								}
+							}
+							Some("lumber") => {
+								let radius = match words.next() {
+									Some(radius) => radius.parse()?,
+									None => 32,
+								};
+
+								let mut data = data.lock().await;
+								let (time, pos) = data.owner_pos;
+								if time.elapsed() > Duration::from_secs(30) {
+									data.say.push_back("i don't know where you are".to_owned());
+									return Ok(());
+								}
+
+								let trees =
+									jobs::lumber(pos.to_block_pos_floor(), radius, &data.world);
+								data.say.push_back(format!("felling {} trees", trees.len()));
+
+								let mut job = graph::Job::new();
+								for tree in trees {
+									job.push(tree);
+								}
+								data.schedule(job);
+							}
+							Some("pillar") => {
+								let pos = parse_block_pos(&mut words)?;
+								let to_y = words
+									.next()
+									.ok_or_else(|| anyhow!("expected a y level"))?
+									.parse()?;
 
-								println!("{:?}", data.task_queue);
+								let mut job = graph::Job::new();
+								job.push(Task::Pillar { pos, to_y });
+								data.lock().await.schedule(job);
 							}
-							Some("stop") => {
+							Some("bridge") => {
+								let from = parse_block_pos(&mut words)?;
+								let to = parse_block_pos(&mut words)?;
+
+								let mut job = graph::Job::new();
+								job.push(Task::Bridge { from, to });
+								data.lock().await.schedule(job);
+							}
+							Some("what") => {
+								let pos = parse_block_pos(&mut words)?;
+
 								let mut data = data.lock().await;
-								data.task_queue.clear();
-								data.per_inst.clear();
+								let reply = match data.world.get(pos) {
+									Some(state) => format!(
+										"{} {} {} is {:?}",
+										pos.x,
+										pos.y,
+										pos.z,
+										azalea::registry::Block::from(state)
+									),
+									None => {
+										format!("nobody's seen {} {} {} yet", pos.x, pos.y, pos.z)
+									}
+								};
+								data.say.push_back(reply);
 							}
 							_ => {}
 						},
@@ -144,6 +1529,70 @@ pub async fn start_server(owner: String) -> anyhow::Result<()> {
 			}
 		});
 	}
+	{
+		let data = data.clone();
+		let clients = clients.clone();
+		// guard routine, keeps track of the hostile mobs near whatever's being guarded
+		tokio::spawn(async move {
+			loop {
+				tokio::time::sleep(Duration::from_millis(500)).await;
+
+				let request = {
+					let mut data = data.lock().await;
+					let center = data.guard_center();
+					let Some(guard) = data.guard.as_mut() else {
+						continue;
+					};
+					let radius = guard.radius;
+					// nobody's seen the owner in a while, the guards stand down until someone does
+					let lost = center.is_none();
+					let changed = guard.lost_center != lost;
+					guard.lost_center = lost;
+					if changed {
+						data.say.push_back(
+							if lost {
+								"lost track of you, not guarding until i find you again"
+							} else {
+								"found you, back to guarding"
+							}
+							.to_owned(),
+						);
+					}
+					let Some(center) = center else {
+						continue;
+					};
+					ClientboundPacket::FindMobs { center, radius }
+				};
+				send_per_process(&clients, &request).await;
+			}
+		});
+	}
+	{
+		let data = data.clone();
+		let clients = clients.clone();
+		// farm routine
+		tokio::spawn(async move {
+			loop {
+				tokio::time::sleep(FARM_INTERVAL).await;
+
+				let scans = {
+					let mut data = data.lock().await;
+					data.schedule_harvests();
+					data.farms
+						.iter()
+						.map(|farm| ClientboundPacket::Scan {
+							from: farm.from,
+							to: farm.to,
+						})
+						.collect::<Vec<_>>()
+				};
+				// so the next round knows about crops nobody's been close enough to see grow
+				for scan in scans {
+					send_per_process(&clients, &scan).await;
+				}
+			}
+		});
+	}
 	{
 		let data = data.clone();
 		let clients = clients.clone();
@@ -153,37 +1602,11 @@ pub async fn start_server(owner: String) -> anyhow::Result<()> {
 				loop {
 					tokio::time::sleep(Duration::from_millis(300)).await;
 
-					{
-						let request = ClientboundPacket::Find {
-							username: data.lock().await.owner.clone(),
-						};
-
-						for client in clients.lock().await.iter() {
-							let mut client = client.lock().await;
-							client.write_as_packet(&request).await?;
-
-							// the response shouldn't be handled by the general request handler because it's locked even before
-							// we send the request and we hold that lock till after their response has been read
-							let resp: ServerboundPacket = client.read_as_packet().await?;
-							match resp {
-								ServerboundPacket::ReportPosition { username, report }
-									if username == data.lock().await.owner =>
-								{
-									match report {
-										PosReport::NotHere => continue,
-										PosReport::Found(pos) => {
-											let mut data = data.lock().await;
-											data.owner_pos = (Instant::now(), pos);
-											break;
-										}
-									}
-								}
-								_ => {
-									eprintln!("whereis thread dropped non-report packet: {resp:?}")
-								}
-							}
-						}
-					}
+					// responses are handled with the rest of the requests
+					let request = ClientboundPacket::Find {
+						username: data.lock().await.owner.clone(),
+					};
+					send_per_process(&clients, &request).await;
 				}
 			};
 			match internal().await {
@@ -207,44 +1630,54 @@ pub async fn start_server(owner: String) -> anyhow::Result<()> {
 						continue;
 					}
 				};
-				let mut hi = async || -> anyhow::Result<()> {
+				let mut hi = async || -> anyhow::Result<(i32, u32, String)> {
 					let hello: ServerboundHelloPacket = socket.read_as_packet().await?;
 
 					let name = {
 						let mut data = data.lock().await;
-						data.namegen.next()
+						let name = data.namegen.next();
+						if let Some((i, name)) = &name {
+							data.bots.insert(name.clone());
+							data.usernames.insert(*i as _, name.clone());
+						}
+						name
 					};
 					let (i, name) = name.expect("namegen is never supposed to return none");
 					println!("[{hello:?}] hello {i}: {name}");
 
 					let hello_resp = ClientboundHelloPacket {
-						name,
+						name: name.clone(),
 						inst_id: i as _,
 					};
 					socket.write_as_packet(&hello_resp).await?;
-					Ok(())
+					Ok((i as _, hello.pid, name))
 				};
-				match hi().await {
+				let (inst_id, pid, username) = match hi().await {
 					Ok(a) => a,
 					Err(err) => {
-						eprintln!("error while exchanging Hello packets: {err}")
+						eprintln!("error while exchanging Hello packets: {err}");
+						continue;
 					}
-				}
+				};
 
-				let socket = Arc::new(Mutex::new(socket));
+				let (mut read, write) = socket.into_split();
+				let write = Arc::new(Mutex::new(write));
 				{
-					clients.lock().await.push(socket.clone());
+					clients.lock().await.push(Connection {
+						inst_id,
+						pid,
+						username,
+						write: write.clone(),
+					});
 				}
 
 				let data = data.clone();
+				let clients = clients.clone();
 				let handle_chat = handle_chat.clone();
 				tokio::spawn(async move {
-					let internal = async || -> anyhow::Result<()> {
+					let mut internal = async || -> anyhow::Result<()> {
 						loop {
-							let packet: ServerboundPacket = {
-								let mut socket = socket.lock().await;
-								socket.read_as_packet().await?
-							};
+							let packet: ServerboundPacket = read.read_as_packet().await?;
 
 							match packet {
 								ServerboundPacket::ChatMessage {
@@ -265,20 +1698,70 @@ pub async fn start_server(owner: String) -> anyhow::Result<()> {
 
 									handle_chat(sender, content).await?;
 								}
-								ServerboundPacket::Agro { uuid } => {
+								ServerboundPacket::Agro {
+									uuid,
+									player,
+									damage,
+								} => {
 									let mut data = data.lock().await;
-									data.per_inst.new_task_times(Task::Attack(uuid), 3);
+									let ServerData {
+										agro, bots, owner, ..
+									} = &mut *data;
+									let allies =
+										bots.iter().map(|bot| bot.as_str()).chain([owner.as_str()]);
+									if agro.should_agro(uuid, player.as_deref(), damage, allies) {
+										data.enemies.seen(uuid, None);
+									}
 								}
 								ServerboundPacket::RequestTask { inst_id } => {
 									let task = {
 										let mut data = data.lock().await;
+										data.active.insert(inst_id, Instant::now());
+										data.schedule_collects();
+										data.schedule_replaces();
 
-										if let Some(per_inst) = data.per_inst.task_for(inst_id) {
+										if let Some(message) = data.say.pop_front() {
+											Task::Say(message)
+										} else if let Some(per_inst) =
+											data.per_inst.task_for(inst_id)
+										{
 											per_inst
+										} else if let Some(directed) = data
+											.directed
+											.get_mut(&inst_id)
+											.and_then(|tasks| tasks.pop_front())
+										{
+											data.handed_out.insert(inst_id, directed.clone());
+											directed
+										} else if let Some(uuid) = data
+											.can_fight(inst_id)
+											.then(|| data.enemies.target_for(inst_id, None))
+											.flatten()
+										{
+											Task::Attack(uuid)
+										} else if let Some(attack) = data.guard_task_for(inst_id) {
+											// whatever job it's on waits until the mob is dealt with
+											attack
+										} else if let Some(pick_up) = data.pick_up_task_for() {
+											pick_up
+										} else if let Some(craft) = data.restock_craft_for(inst_id)
+										{
+											craft
+										} else if let Some(stash) = data.stash_task_for(inst_id) {
+											stash
+										} else if let Some(region) = data.region_task_for(inst_id) {
+											region
+										} else if let Some(gather) = data.gather_task_for(inst_id) {
+											gather
 										} else {
 											let from_queue = data.task_queue.pop_front();
 											if let Some(from_queue) = from_queue {
 												from_queue
+											} else if let Some(post) = data.guard_post(inst_id) {
+												Task::Goto(RadiusGoal {
+													pos: post,
+													radius: 2.0,
+												})
 											} else {
 												let (time, pos) = data.owner_pos;
 												if time.elapsed() < Duration::from_secs(30) {
@@ -290,17 +1773,191 @@ pub async fn start_server(owner: String) -> anyhow::Result<()> {
 										}
 									};
 
-									let mut socket = socket.lock().await;
+									let mut write = write.lock().await;
 									let response = ClientboundPacket::AssignTask(Some(task));
-									socket.write_as_packet(response).await?;
+									write.write_as_packet(response).await?;
 								}
-								ServerboundPacket::ReportPosition { .. } => {
-									// handled elsewhere
+								ServerboundPacket::TaskDone { inst_id, result } => {
+									let mut data = data.lock().await;
+									if let Some(task) = data.handed_out.remove(&inst_id) {
+										data.directed_done(inst_id, task, &result);
+									}
+									for gather in data.gathering.iter_mut() {
+										if gather.assigned.contains_key(&inst_id)
+											&& matches!(
+												result,
+												TaskResult::Failed(_) | TaskResult::NoTool(_)
+											) {
+											gather.stuck.insert(inst_id, Instant::now());
+										}
+									}
+									let finished = match result {
+										TaskResult::Done => data.graph.done(inst_id),
+										TaskResult::Failed(err) => {
+											// couldn't see it or ran away, it'll get a new one
+											data.enemies.release(inst_id);
+											if let Some(guard) = data.guard.as_mut() {
+												guard.mobs.release(inst_id);
+											}
+
+											let id = data.graph.leased_by(inst_id);
+											let gave_up = data.graph.failed(inst_id);
+											if gave_up.is_some() {
+												eprintln!(
+													"gave up on a task {inst_id} kept failing: {err}"
+												);
+											} else if let Some(id) = id {
+												data.put_back(id, inst_id);
+											}
+											gave_up
+										}
+										TaskResult::Interrupted(reason) => {
+											println!("{reason}");
+											data.enemies.release(inst_id);
+											if let Some(guard) = data.guard.as_mut() {
+												guard.mobs.release(inst_id);
+											}
+											data.release_task(inst_id);
+											None
+										}
+										TaskResult::NoTool(err) => {
+											println!("{err}");
+											// someone with the right tool gets it, if nobody has one it waits for someone to get one
+											if data.graph.exclude(inst_id)
+												>= data.active_instances()
+											{
+												data.say.push_back(format!(
+													"{err}, and neither does anyone else. it'll wait until someone gets one"
+												));
+											}
+											data.release_task(inst_id);
+											None
+										}
+									};
+									if let Some(task) = finished {
+										data.finished(&task);
+									}
+								}
+								ServerboundPacket::ReportPosition { username, report } => {
+									let mut data = data.lock().await;
+									if username == data.owner {
+										if let PosReport::Found(pos) = report {
+											data.owner_pos = (Instant::now(), pos);
+										}
+									}
+								}
+								ServerboundPacket::ReportArea { from, to, states } => {
+									let mut data = data.lock().await;
+									data.world.insert_area(from, to, states);
+									data.schedule_replaces();
+								}
+								ServerboundPacket::ReportBlocks { blocks } => {
+									let mut data = data.lock().await;
+									data.world.insert_blocks(blocks);
+								}
+								ServerboundPacket::EntityGone { uuid } => {
+									let mut data = data.lock().await;
+									data.enemies.forget(uuid);
+									if let Some(guard) = data.guard.as_mut() {
+										guard.mobs.forget(uuid);
+									}
+								}
+								ServerboundPacket::ReportMobs { mobs } => {
+									let mut data = data.lock().await;
+									if let Some(guard) = data.guard.as_mut() {
+										guard.mobs_seen(mobs);
+									}
+								}
+								ServerboundPacket::Died { inst_id, pos } => {
+									println!("{inst_id} died at {pos}");
+									let mut data = data.lock().await;
+									// it's not coming back to it
+									data.release_task(inst_id);
+									data.regions.release(inst_id);
+									data.enemies.release(inst_id);
+									if let Some(guard) = data.guard.as_mut() {
+										guard.mobs.release(inst_id);
+									}
+									if data.recover_items {
+										data.deaths.push_back((pos, Instant::now()));
+									}
+								}
+								ServerboundPacket::ReportVitals { inst_id, vitals } => {
+									let mut data = data.lock().await;
+									data.vitals.insert(inst_id, vitals);
+								}
+								ServerboundPacket::ReportContainer { pos, items } => {
+									let mut data = data.lock().await;
+									data.index.update(pos, items);
+								}
+								ServerboundPacket::ReportInventory {
+									inst_id,
+									items,
+									free_slots,
+								} => {
+									let mut data = data.lock().await;
+									let items = items.into_iter().collect::<HashMap<_, _>>();
+									// a new tool might be what the tasks nobody could do were waiting for
+									let new_tool = items.iter().any(|(item, count)| {
+										pathfind::is_tool(*item)
+											&& data
+												.inventories
+												.get(&inst_id)
+												.and_then(|old| old.get(item))
+												.is_none_or(|old| old < count)
+									});
+									if new_tool {
+										data.graph.clear_exclusions();
+									}
+									for gather in data.gathering.iter_mut() {
+										if let Some(held) = gather.assigned.remove(&inst_id) {
+											let now = items.get(&gather.item).copied().unwrap_or(0);
+											gather.collected += (now - held).max(0);
+										}
+									}
+									data.check_receipts(inst_id, &items);
+									data.free_slots.insert(inst_id, free_slots);
+									data.inventories.insert(inst_id, items);
+								}
+								ServerboundPacket::ReportChunk(chunk) => {
+									let mut data = data.lock().await;
+									data.world.insert_chunk(chunk);
+									data.schedule_replaces();
 								}
 							}
 						}
 					};
-					match internal().await {
+					let res = internal().await;
+					{
+						// whatever it was working on goes back up for grabs
+						let mut data = data.lock().await;
+						data.active.remove(&inst_id);
+						data.release_task(inst_id);
+						data.regions.release(inst_id);
+						data.inventories.remove(&inst_id);
+						data.free_slots.remove(&inst_id);
+						// nobody's loading its furnaces anymore
+						let directed = data.directed.remove(&inst_id).unwrap_or_default();
+						for task in directed.into_iter().chain(data.handed_out.remove(&inst_id)) {
+							if let Task::LoadFurnace { furnace, .. } = task {
+								data.smelting.not_loaded(furnace);
+							}
+						}
+						data.receipts.retain(|receipt| receipt.inst_id != inst_id);
+						data.usernames.remove(&inst_id);
+						data.craft_attempts.retain(|(id, _), _| *id != inst_id);
+						data.stash.forget(inst_id);
+						data.vitals.remove(&inst_id);
+						data.enemies.release(inst_id);
+						if let Some(guard) = data.guard.as_mut() {
+							guard.mobs.release(inst_id);
+						}
+					}
+					{
+						let mut clients = clients.lock().await;
+						clients.retain(|client| client.inst_id != inst_id);
+					}
+					match res {
 						Ok(a) => a,
 						Err(err) => {
 							eprintln!("server error while handling {addr}: {err}");
@@ -315,3 +1972,63 @@ pub async fn start_server(owner: String) -> anyhow::Result<()> {
 		Ok(())
 	}
 }
+
+/// accepts both `stone` and `minecraft:stone`
+fn parse_block(word: Option<&str>) -> anyhow::Result<azalea::registry::Block> {
+	parse_id(word.ok_or_else(|| anyhow!("expected a block"))?, "block")
+}
+
+/// north, south, east or west as (x, z)
+fn parse_direction(word: Option<&str>) -> anyhow::Result<(i32, i32)> {
+	match word {
+		Some("north") => Ok((0, -1)),
+		Some("south") => Ok((0, 1)),
+		Some("east") => Ok((1, 0)),
+		Some("west") => Ok((-1, 0)),
+		Some(word) => Err(anyhow!("{word} isn't north, south, east or west")),
+		None => Err(anyhow!("expected a direction")),
+	}
+}
+
+/// minecraft:diamond_ore -> diamond_ore
+fn block_name(block: azalea::registry::Block) -> String {
+	let id = block.to_string();
+	id.strip_prefix("minecraft:").unwrap_or(&id).to_owned()
+}
+
+/// minecraft:cobblestone -> cobblestone
+fn item_name(item: Item) -> String {
+	let id = item.to_string();
+	id.strip_prefix("minecraft:").unwrap_or(&id).to_owned()
+}
+
+/// accepts both `cobblestone` and `minecraft:cobblestone`
+fn parse_item(word: Option<&str>) -> anyhow::Result<Item> {
+	parse_id(word.ok_or_else(|| anyhow!("expected an item"))?, "item")
+}
+
+/// a registry entry, minecraft: is optional
+fn parse_id<T: std::str::FromStr>(word: &str, what: &str) -> anyhow::Result<T> {
+	let id = if word.contains(':') {
+		word.to_owned()
+	} else {
+		format!("minecraft:{word}")
+	};
+	T::from_str(&id).map_err(|_| anyhow!("there's no {what} called {word}"))
+}
+
+fn parse_block_pos<'a>(words: &mut impl Iterator<Item = &'a str>) -> anyhow::Result<BlockPos> {
+	let x = words
+		.next()
+		.ok_or_else(|| anyhow!("expected x coordinate"))?
+		.parse()?;
+	let y = words
+		.next()
+		.ok_or_else(|| anyhow!("expected y coordinate"))?
+		.parse()?;
+	let z = words
+		.next()
+		.ok_or_else(|| anyhow!("expected z coordinate"))?
+		.parse()?;
+	Ok(BlockPos { x, y, z })
+}
@@ -0,0 +1,294 @@
+use std::collections::VecDeque;
+
+use azalea::BlockPos;
+
+use super::graph::{NodeId, TaskGraph};
+
+/// don't bother splitting a region if the half we'd take would be smaller than this
+const MIN_STEAL: usize = 8;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Axis {
+	X,
+	Z,
+}
+impl Axis {
+	pub fn of(&self, pos: BlockPos) -> i32 {
+		match self {
+			Self::X => pos.x,
+			Self::Z => pos.z,
+		}
+	}
+	pub fn other(&self) -> Self {
+		match self {
+			Self::X => Self::Z,
+			Self::Z => Self::X,
+		}
+	}
+}
+
+/// splits bulk jobs into slices so every bot gets its own part of the area
+/// and they don't stand on or mine the blocks next to each other \
+/// regions only decide who gets what, whether a task can be handed out yet is up to the TaskGraph
+#[derive(Clone, Debug, Default)]
+pub struct Regions {
+	regions: Vec<Region>,
+}
+impl Regions {
+	/// splits the tasks into `slices` slices along the longer horizontal axis of the area they cover \
+	/// tasks keep their order inside a slice, the ones without a position all go in the first one
+	pub fn add(
+		&mut self,
+		tasks: impl IntoIterator<Item = (NodeId, Option<BlockPos>)>,
+		slices: usize,
+	) {
+		let tasks = tasks.into_iter().collect::<Vec<_>>();
+		if tasks.is_empty() {
+			return;
+		}
+
+		let bounds = tasks.iter().filter_map(|(_, pos)| *pos).fold(
+			None,
+			|bounds: Option<(BlockPos, BlockPos)>, pos| {
+				Some(match bounds {
+					Some((min, max)) => (
+						BlockPos::new(min.x.min(pos.x), min.y.min(pos.y), min.z.min(pos.z)),
+						BlockPos::new(max.x.max(pos.x), max.y.max(pos.y), max.z.max(pos.z)),
+					),
+					None => (pos, pos),
+				})
+			},
+		);
+		let (min, max) = bounds.unwrap_or_default();
+
+		let axis = if max.x - min.x >= max.z - min.z {
+			Axis::X
+		} else {
+			Axis::Z
+		};
+		let len = axis.of(max) - axis.of(min) + 1;
+		let slices = (slices.max(1) as i32).min(len);
+		let width = (len + slices - 1) / slices;
+
+		let mut sliced = (0..slices).map(|_| VecDeque::new()).collect::<Vec<_>>();
+		for task in tasks {
+			let i = task
+				.1
+				.map(|pos| (axis.of(pos) - axis.of(min)) / width)
+				.unwrap_or(0);
+			sliced[i as usize].push_back(task);
+		}
+
+		self.regions.extend(
+			sliced
+				.into_iter()
+				.filter(|tasks| !tasks.is_empty())
+				.map(|tasks| Region {
+					owner: None,
+					axis,
+					tasks,
+				}),
+		);
+	}
+
+	/// the next task for this instance that's ready to go. instances keep their region until it runs out,
+	/// then take over a free region or split off half of the biggest one \
+	/// only if none of that has anything ready, it helps out with whatever's ready in someone else's region
+	pub fn task_for(&mut self, inst_id: i32, graph: &TaskGraph) -> Option<NodeId> {
+		for region in self.regions.iter_mut() {
+			region.tasks.retain(|(id, _)| graph.contains(*id));
+		}
+		// someone else's empty region stays theirs, tasks they fail go back in it
+		self.regions.retain(|region| {
+			!region.tasks.is_empty() || region.owner.is_some_and(|owner| owner != inst_id)
+		});
+
+		let own = self
+			.regions
+			.iter_mut()
+			.filter(|region| region.owner == Some(inst_id))
+			.find_map(|region| region.take_ready(graph, inst_id));
+		if own.is_some() {
+			return own;
+		}
+
+		if !self
+			.regions
+			.iter()
+			.any(|region| region.owner == Some(inst_id))
+		{
+			let free = self
+				.regions
+				.iter_mut()
+				.find(|region| region.owner.is_none());
+			if let Some(free) = free {
+				free.owner = Some(inst_id);
+				if let Some(id) = free.take_ready(graph, inst_id) {
+					return Some(id);
+				}
+			}
+		}
+
+		let biggest = self
+			.regions
+			.iter_mut()
+			.filter(|region| region.owner != Some(inst_id))
+			.max_by_key(|region| region.tasks.len());
+		if let Some(mut stolen) = biggest.and_then(|biggest| biggest.split()) {
+			stolen.owner = Some(inst_id);
+			let id = stolen.take_ready(graph, inst_id);
+			self.regions.push(stolen);
+			if id.is_some() {
+				return id;
+			}
+		}
+
+		self.regions
+			.iter_mut()
+			.find_map(|region| region.take_ready(graph, inst_id))
+	}
+
+	/// a task that was handed out but not done goes back up for grabs \
+	/// at the front of the region of the instance that had it, so it stays with the tasks around it
+	pub fn put_back(&mut self, id: NodeId, pos: Option<BlockPos>, inst_id: i32) {
+		let own = self
+			.regions
+			.iter_mut()
+			.find(|region| region.owner == Some(inst_id));
+		match own {
+			Some(own) => own.tasks.push_front((id, pos)),
+			None => self.regions.push(Region {
+				owner: None,
+				axis: Axis::X,
+				tasks: VecDeque::from([(id, pos)]),
+			}),
+		}
+	}
+
+	/// the tasks left in the instance's region
+	pub fn owned_by(&self, inst_id: i32) -> impl Iterator<Item = NodeId> + '_ {
+		self.regions
+			.iter()
+			.filter(move |region| region.owner == Some(inst_id))
+			.flat_map(|region| region.tasks.iter().map(|(id, _)| *id))
+	}
+
+	/// gives up the region owned by this instance so someone else can pick it up
+	pub fn release(&mut self, inst_id: i32) {
+		for region in self.regions.iter_mut() {
+			if region.owner == Some(inst_id) {
+				region.owner = None;
+			}
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.regions.clear()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.regions.iter().all(|region| region.tasks.is_empty())
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Region {
+	owner: Option<i32>,
+	/// the axis the region was sliced along, splitting happens along it too
+	axis: Axis,
+	tasks: VecDeque<(NodeId, Option<BlockPos>)>,
+}
+impl Region {
+	/// removes and returns the first task that's ready to be handed to the instance
+	fn take_ready(&mut self, graph: &TaskGraph, inst_id: i32) -> Option<NodeId> {
+		let i = self
+			.tasks
+			.iter()
+			.position(|(id, _)| graph.is_ready_for(*id, inst_id))?;
+		self.tasks.remove(i).map(|(id, _)| id)
+	}
+
+	/// takes the far half of the remaining tasks, keeping the order they'd have been executed in
+	fn split(&mut self) -> Option<Region> {
+		if self.tasks.len() < MIN_STEAL * 2 {
+			return None;
+		}
+
+		for axis in [self.axis, self.axis.other()] {
+			let coords = self
+				.tasks
+				.iter()
+				.filter_map(|(_, pos)| *pos)
+				.map(|pos| axis.of(pos));
+			let (min, max) = coords.fold((i32::MAX, i32::MIN), |(min, max), c| {
+				(min.min(c), max.max(c))
+			});
+			if min >= max {
+				continue;
+			}
+			let mid = min + (max - min + 1) / 2;
+			let stays = |task: &(NodeId, Option<BlockPos>)| match task.1 {
+				Some(pos) => axis.of(pos) < mid,
+				None => true,
+			};
+
+			if self.tasks.iter().filter(|task| !stays(task)).count() < MIN_STEAL {
+				return None;
+			}
+
+			let (kept, stolen): (VecDeque<_>, VecDeque<_>) =
+				std::mem::take(&mut self.tasks).into_iter().partition(stays);
+			self.tasks = kept;
+
+			return Some(Region {
+				owner: None,
+				axis,
+				tasks: stolen,
+			});
+		}
+		None
+	}
+}
+
+#[test]
+fn failed_tasks_are_handed_out_again() {
+	use super::graph::Job;
+	use crate::tasks::Task;
+
+	let mut graph = TaskGraph::default();
+	let mut regions = Regions::default();
+	let pos = BlockPos::new(0, 64, 0);
+	let ids = graph.add(Job::mine([pos]));
+	regions.add(ids.iter().map(|id| (*id, Some(pos))), 1);
+
+	let id = regions.task_for(1, &graph).unwrap();
+	assert_eq!(graph.lease(id, 1), Some(Task::Mine(pos)));
+	assert_eq!(graph.failed(1), None);
+	regions.put_back(id, Some(pos), 1);
+
+	let id = regions.task_for(1, &graph).unwrap();
+	assert_eq!(graph.lease(id, 1), Some(Task::Mine(pos)));
+	graph.done(1);
+	assert!(graph.is_empty());
+	assert_eq!(regions.task_for(1, &graph), None);
+}
+
+#[test]
+fn idle_instances_split_the_biggest_region() {
+	use super::graph::Job;
+
+	let mut graph = TaskGraph::default();
+	let mut regions = Regions::default();
+	let positions = (0..32).map(|x| BlockPos::new(x, 64, 0)).collect::<Vec<_>>();
+	let ids = graph.add(Job::mine(positions.iter().copied()));
+	regions.add(
+		ids.iter()
+			.map(|id| (*id, graph.task(*id).and_then(|task| task.pos()))),
+		1,
+	);
+
+	let first = regions.task_for(1, &graph).unwrap();
+	graph.lease(first, 1);
+	let stolen = regions.task_for(2, &graph).unwrap();
+	let pos = graph.task(stolen).and_then(|task| task.pos()).unwrap();
+	assert!(pos.x >= 16);
+}
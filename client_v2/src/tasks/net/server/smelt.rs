@@ -0,0 +1,139 @@
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+use azalea::{
+	BlockPos,
+	registry::{Block, Item},
+};
+
+use super::world::World;
+use crate::tasks::{Task, net::cuboid};
+
+/// how long a furnace takes per item, blast furnaces and smokers are faster but this is only for planning
+const SMELT_TIME: Duration = Duration::from_secs(10);
+/// how long it takes the bot to get to the furnace and load it, roughly
+const LOAD_TIME: Duration = Duration::from_secs(30);
+/// a furnace's input slot holds a stack
+const MAX_LOAD: i32 = 64;
+
+/// furnaces the bots load and empty
+#[derive(Debug, Default)]
+pub struct Smelting {
+	/// where the furnaces are, nothing's smelted until it's set
+	pub area: Option<(BlockPos, BlockPos)>,
+	/// furnace -> when what's in it should be done, None once someone's been sent to empty it
+	loaded: HashMap<BlockPos, Option<Instant>>,
+}
+impl Smelting {
+	/// the furnaces in the area that can smelt the input and have nothing in them, smokers and blast furnaces first
+	pub fn furnaces(&self, input: Item, world: &World) -> Vec<BlockPos> {
+		let Some((from, to)) = self.area else {
+			return Vec::new();
+		};
+		let mut furnaces = cuboid(from, to)
+			.filter(|pos| !self.loaded.contains_key(pos))
+			.filter_map(|pos| Some((pos, Block::from(world.get(pos)?))))
+			.filter(|(_, block)| accepts(*block, input))
+			.collect::<Vec<_>>();
+		furnaces.sort_by_key(|(_, block)| *block == Block::Furnace);
+		furnaces.into_iter().map(|(pos, _)| pos).collect()
+	}
+
+	/// splits what the instances have on them between the furnaces, a stack per furnace at most \
+	/// returns a task for every instance that has to load a furnace
+	pub fn plan(
+		&mut self,
+		input: Item,
+		count: i32,
+		holders: Vec<(i32, i32)>,
+		world: &World,
+	) -> Vec<(i32, Task)> {
+		let mut furnaces = self.furnaces(input, world).into_iter();
+		let mut tasks = Vec::new();
+		let mut left = count;
+		'holders: for (inst_id, mut have) in holders {
+			while have > 0 && left > 0 {
+				let Some(furnace) = furnaces.next() else {
+					break 'holders;
+				};
+				let load = have.min(left).min(MAX_LOAD);
+				have -= load;
+				left -= load;
+
+				self.loaded.insert(
+					furnace,
+					Some(Instant::now() + LOAD_TIME + SMELT_TIME * load as u32),
+				);
+				tasks.push((
+					inst_id,
+					Task::LoadFurnace {
+						furnace,
+						input,
+						count: load,
+					},
+				));
+			}
+		}
+		tasks
+	}
+
+	/// furnaces that should be done by now, they're only returned once
+	pub fn due(&mut self) -> Vec<BlockPos> {
+		let mut due = Vec::new();
+		for (furnace, done_at) in self.loaded.iter_mut() {
+			if done_at.is_some_and(|done_at| Instant::now() >= done_at) {
+				*done_at = None;
+				due.push(*furnace);
+			}
+		}
+		due
+	}
+	/// someone emptied the furnace, or gave up on it
+	pub fn collected(&mut self, furnace: BlockPos) {
+		self.loaded.remove(&furnace);
+	}
+	/// loading it didn't work out, so there's nothing to collect and it's free for the next job
+	pub fn not_loaded(&mut self, furnace: BlockPos) {
+		self.loaded.remove(&furnace);
+	}
+
+	pub fn clear(&mut self) {
+		self.loaded.clear();
+	}
+}
+
+/// smokers only take food and blast furnaces only take ores, a furnace takes anything
+fn accepts(furnace: Block, input: Item) -> bool {
+	match furnace {
+		Block::Furnace => true,
+		Block::Smoker => {
+			matches!(
+				input,
+				Item::Beef
+					| Item::Porkchop
+					| Item::Chicken | Item::Mutton
+					| Item::Rabbit | Item::Cod
+					| Item::Salmon | Item::Potato
+					| Item::Kelp
+			)
+		}
+		Block::BlastFurnace => {
+			matches!(
+				input,
+				Item::RawIron | Item::RawCopper | Item::RawGold | Item::AncientDebris
+			) || input.to_string().ends_with("_ore")
+		}
+		_ => false,
+	}
+}
+
+#[test]
+fn picks_the_furnace_for_the_input() {
+	assert!(accepts(Block::Smoker, Item::Beef));
+	assert!(!accepts(Block::Smoker, Item::RawIron));
+	assert!(accepts(Block::BlastFurnace, Item::DeepslateGoldOre));
+	assert!(!accepts(Block::BlastFurnace, Item::Sand));
+	assert!(accepts(Block::Furnace, Item::Sand));
+}
@@ -0,0 +1,110 @@
+use std::{
+	collections::{HashMap, HashSet},
+	str::FromStr,
+	time::{Duration, Instant},
+};
+
+use azalea::{BlockPos, registry::Item};
+
+use super::world::{World, is_container};
+use crate::tasks::{Task, net::cuboid, survival::FOODS};
+
+/// a bot that just stashed only goes back this soon if its inventory is full again
+const FULL_COOLDOWN: Duration = Duration::from_secs(30);
+/// and this soon if it's only missing something from the restock list, the chests might not have it
+const RESTOCK_COOLDOWN: Duration = Duration::from_secs(120);
+
+/// when bots go empty their inventory into the storage chests and what they take back out
+#[derive(Clone, Debug)]
+pub struct StashPolicy {
+	/// where the chests are, nobody stashes anything until it's set
+	pub storage: Option<(BlockPos, BlockPos)>,
+	/// never put in the chests
+	pub keep: HashSet<Item>,
+	/// item -> how many of it every bot should have on it
+	pub restock: HashMap<Item, i32>,
+	/// inst_id -> when it was last sent to the chests
+	last_stash: HashMap<i32, Instant>,
+}
+impl Default for StashPolicy {
+	fn default() -> Self {
+		let tools = ["wooden", "stone", "iron", "golden", "diamond", "netherite"]
+			.into_iter()
+			.flat_map(|material| {
+				["pickaxe", "axe", "shovel", "sword"]
+					.map(|tool| format!("minecraft:{material}_{tool}"))
+			})
+			.filter_map(|id| Item::from_str(&id).ok());
+
+		Self {
+			storage: None,
+			keep: tools
+				.chain(FOODS.iter().copied())
+				.chain([Item::Torch])
+				.collect(),
+			restock: HashMap::new(),
+			last_stash: HashMap::new(),
+		}
+	}
+}
+impl StashPolicy {
+	/// every container in the storage area we know about
+	pub fn chests(&self, world: &World) -> Vec<BlockPos> {
+		let Some((from, to)) = self.storage else {
+			return Vec::new();
+		};
+		cuboid(from, to)
+			.filter(|pos| world.get(*pos).map(is_container).unwrap_or(false))
+			.collect()
+	}
+
+	/// sends the bot to the chests if its inventory's full or it's missing something it should have \
+	/// `needed` is kept on top of the keep list, it's what the bot's own tasks are going to use
+	pub fn task_for(
+		&mut self,
+		inst_id: i32,
+		items: &HashMap<Item, i32>,
+		free_slots: i32,
+		needed: HashSet<Item>,
+		world: &World,
+	) -> Option<Task> {
+		let full = free_slots == 0;
+		let take = self
+			.restock
+			.iter()
+			.filter_map(|(item, want)| {
+				let have = items.get(item).copied().unwrap_or_default();
+				(have < *want).then_some((*item, want - have))
+			})
+			.collect::<Vec<_>>();
+		if !full && take.is_empty() {
+			return None;
+		}
+
+		let cooldown = if full {
+			FULL_COOLDOWN
+		} else {
+			RESTOCK_COOLDOWN
+		};
+		if let Some(last) = self.last_stash.get(&inst_id) {
+			if last.elapsed() < cooldown {
+				return None;
+			}
+		}
+
+		let chests = self.chests(world);
+		if chests.is_empty() {
+			return None;
+		}
+		self.last_stash.insert(inst_id, Instant::now());
+		Some(Task::Stash {
+			chests,
+			keep: self.keep.union(&needed).copied().collect(),
+			take,
+		})
+	}
+
+	pub fn forget(&mut self, inst_id: i32) {
+		self.last_stash.remove(&inst_id);
+	}
+}
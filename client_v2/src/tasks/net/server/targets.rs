@@ -0,0 +1,89 @@
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+use azalea::Vec3;
+use uuid::Uuid;
+
+/// enemies the swarm is fighting and who's fighting which \
+/// every instance gets the enemy with the fewest attackers, so they spread out instead of piling onto one
+#[derive(Clone, Debug)]
+pub struct Targets {
+	/// how long an enemy is fought after it was last reported
+	forget_after: Duration,
+	/// instances beyond this many on every enemy are left to do something else
+	max_attackers: usize,
+	/// uuid -> where it was if we know, and when it was last reported
+	enemies: HashMap<Uuid, (Option<Vec3>, Instant)>,
+	/// inst_id -> the enemy it's fighting
+	attackers: HashMap<i32, Uuid>,
+}
+impl Targets {
+	pub fn new(forget_after: Duration, max_attackers: usize) -> Self {
+		Self {
+			forget_after,
+			max_attackers,
+			enemies: HashMap::new(),
+			attackers: HashMap::new(),
+		}
+	}
+
+	pub fn seen(&mut self, uuid: Uuid, pos: Option<Vec3>) {
+		let pos = pos.or_else(|| self.enemies.get(&uuid).and_then(|(pos, _)| *pos));
+		self.enemies.insert(uuid, (pos, Instant::now()));
+	}
+	/// the enemy the instance should fight, it sticks with the one it has while it's around
+	pub fn target_for(&mut self, inst_id: i32, near: Option<Vec3>) -> Option<Uuid> {
+		let forget_after = self.forget_after;
+		self.enemies
+			.retain(|_, (_, seen)| seen.elapsed() < forget_after);
+		self.attackers
+			.retain(|_, uuid| self.enemies.contains_key(uuid));
+
+		if let Some(uuid) = self.attackers.get(&inst_id) {
+			return Some(*uuid);
+		}
+
+		let mut counts = HashMap::<Uuid, usize>::new();
+		for uuid in self.attackers.values() {
+			*counts.entry(*uuid).or_default() += 1;
+		}
+		let distance = |pos: &Option<Vec3>| match (pos, near) {
+			(Some(pos), Some(near)) => pos.distance_to(&near),
+			_ => 0.0,
+		};
+		let (uuid, _) = self
+			.enemies
+			.iter()
+			.map(|(uuid, (pos, _))| (uuid, counts.get(uuid).copied().unwrap_or_default(), pos))
+			.filter(|(_, count, _)| *count < self.max_attackers)
+			.min_by(|(_, a_count, a_pos), (_, b_count, b_pos)| {
+				a_count
+					.cmp(b_count)
+					.then(distance(a_pos).total_cmp(&distance(b_pos)))
+			})
+			.map(|(uuid, count, _)| (*uuid, count))?;
+
+		self.attackers.insert(inst_id, uuid);
+		Some(uuid)
+	}
+
+	/// it died or despawned, whoever was fighting it gets a new target
+	pub fn forget(&mut self, uuid: Uuid) {
+		self.enemies.remove(&uuid);
+		self.attackers.retain(|_, attacking| *attacking != uuid);
+	}
+	pub fn set_forget_after(&mut self, forget_after: Duration) {
+		self.forget_after = forget_after;
+	}
+
+	/// the instance isn't fighting anymore, it'll get a new target next time
+	pub fn release(&mut self, inst_id: i32) {
+		self.attackers.remove(&inst_id);
+	}
+	pub fn clear(&mut self) {
+		self.enemies.clear();
+		self.attackers.clear();
+	}
+}
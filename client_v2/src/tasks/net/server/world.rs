@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use azalea::{
+	BlockPos,
+	blocks::{Block, BlockState},
+	core::position::ChunkSectionPos,
+};
+
+use crate::tasks::{
+	Task,
+	farm::is_mature,
+	net::{ChunkReport, cuboid},
+};
+
+/// marks positions in a section we haven't heard about
+const UNKNOWN: u16 = u16::MAX;
+
+/// what the coordinator knows about the world, pieced together from what the clients report
+#[derive(Debug, Default)]
+pub struct World {
+	sections: HashMap<ChunkSectionPos, Section>,
+}
+impl World {
+	/// stores the result of a ClientboundPacket::Scan, `states` being in the order of `cuboid(from, to)`
+	pub fn insert_area(&mut self, from: BlockPos, to: BlockPos, states: Vec<Option<u32>>) {
+		for (pos, state) in cuboid(from, to).zip(states) {
+			if let Some(state) = state.and_then(|state| BlockState::try_from(state).ok()) {
+				self.set(pos, state);
+			}
+		}
+	}
+
+	pub fn insert_blocks(&mut self, blocks: Vec<(BlockPos, u32)>) {
+		for (pos, state) in blocks {
+			if let Ok(state) = BlockState::try_from(state) {
+				self.set(pos, state);
+			}
+		}
+	}
+	pub fn insert_chunk(&mut self, chunk: ChunkReport) {
+		let min_section = chunk.min_y.div_euclid(16);
+		let section_pos = |y: i32| ChunkSectionPos {
+			x: chunk.x,
+			y: min_section + y,
+			z: chunk.z,
+		};
+
+		for y in 0..chunk.height {
+			if !chunk.sections.iter().any(|report| report.y == y) {
+				let section = self.sections.entry(section_pos(y)).or_default();
+				section.states.fill(BlockState::AIR.id() as u16);
+			}
+		}
+		for report in chunk.sections {
+			let section = self.sections.entry(section_pos(report.y)).or_default();
+			// blocks the client didn't know keep whatever we had before
+			match report.indices() {
+				None => {
+					if let Some(Some(state)) = report.palette.first() {
+						section.states.fill(*state as u16);
+					}
+				}
+				Some(indices) => {
+					for (i, index) in indices.into_iter().enumerate() {
+						if let Some(Some(state)) = report.palette.get(index as usize) {
+							section.states[i] = *state as u16;
+						}
+					}
+				}
+			}
+		}
+	}
+
+	pub fn set(&mut self, pos: BlockPos, state: BlockState) {
+		let section = self.sections.entry(ChunkSectionPos::from(pos)).or_default();
+		section.states[Section::index(pos)] = state.id() as u16;
+	}
+	pub fn get(&self, pos: BlockPos) -> Option<BlockState> {
+		let section = self.sections.get(&ChunkSectionPos::from(pos))?;
+		match section.states[Section::index(pos)] {
+			UNKNOWN => None,
+			id => BlockState::try_from(id as u32).ok(),
+		}
+	}
+
+	/// whether we've heard about every position in the area
+	pub fn is_known(&self, from: BlockPos, to: BlockPos) -> bool {
+		cuboid(from, to).all(|pos| self.get(pos).is_some())
+	}
+
+	/// positions in the area we know still have something to mine, top to bottom
+	pub fn remaining(&self, from: BlockPos, to: BlockPos) -> Vec<BlockPos> {
+		let mut remaining = cuboid(from, to)
+			.filter(|pos| self.get(*pos).map(minable).unwrap_or(false))
+			.collect::<Vec<_>>();
+		remaining.sort_by_key(|pos| -pos.y);
+		remaining
+	}
+
+	/// whether we know for sure this task has nothing left to do, so it's not worth sending to anyone
+	pub fn already_done(&self, task: &Task) -> bool {
+		match task {
+			Task::Mine(pos) => self.get(*pos).map(|state| !minable(state)).unwrap_or(false),
+			Task::Place { pos, block } => self
+				.get(*pos)
+				.map(|state| pathfind::placed_as(state, *block))
+				.unwrap_or(false),
+			Task::Plug(pos) => self
+				.get(*pos)
+				.map(|state| !is_liquid(state))
+				.unwrap_or(false),
+			Task::Harvest { pos, .. } => self
+				.get(*pos)
+				.map(|state| !is_mature(state))
+				.unwrap_or(false),
+			_ => false,
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Section {
+	states: Box<[u16; 4096]>,
+}
+impl Default for Section {
+	fn default() -> Self {
+		Self {
+			states: Box::new([UNKNOWN; 4096]),
+		}
+	}
+}
+impl Section {
+	fn index(pos: BlockPos) -> usize {
+		(((pos.y & 15) << 8) | ((pos.z & 15) << 4) | (pos.x & 15)) as usize
+	}
+}
+
+pub fn is_liquid(state: BlockState) -> bool {
+	use azalea::registry::Block as B;
+	matches!(B::from(state), B::Water | B::Lava | B::BubbleColumn)
+}
+
+/// chests, barrels and shulker boxes
+pub fn is_container(state: BlockState) -> bool {
+	use azalea::registry::Block as B;
+	let block = B::from(state);
+	matches!(block, B::Chest | B::TrappedChest | B::Barrel)
+		|| block.to_string().ends_with("shulker_box")
+}
+
+/// any ore, deepslate and nether ones included
+pub fn is_ore(block: azalea::registry::Block) -> bool {
+	let id = block.to_string();
+	id.ends_with("_ore") || id == "minecraft:ancient_debris"
+}
+
+/// false for air, fluids and unbreakable blocks, mining those is a waste of a round trip
+pub fn minable(state: BlockState) -> bool {
+	use azalea::registry::Block as B;
+
+	if state.is_air() {
+		return false;
+	}
+	if matches!(B::from(state), B::Water | B::Lava | B::BubbleColumn) {
+		return false;
+	}
+	let block: Box<dyn Block> = state.into();
+	block.behavior().destroy_time >= 0.0
+}
@@ -0,0 +1,159 @@
+use anyhow::anyhow;
+use azalea::{
+	BlockPos, BotClientExt, Client,
+	inventory::operations::{PickupClick, QuickMoveClick},
+	prelude::ContainerClientExt,
+	registry::Item,
+};
+
+/// the furnace menu's input, fuel and output slots, the player's inventory comes after them
+const INPUT: usize = 0;
+const FUEL: usize = 1;
+const OUTPUT: usize = 2;
+
+/// how many items one of it smelts
+fn fuel_value(item: Item) -> Option<f32> {
+	match item {
+		Item::CoalBlock => Some(80.0),
+		Item::DriedKelpBlock => Some(20.0),
+		Item::BlazeRod => Some(12.0),
+		Item::Coal | Item::Charcoal => Some(8.0),
+		_ if item.to_string().ends_with("_planks") => Some(1.5),
+		_ => None,
+	}
+}
+
+/// puts about `count` of the input in the furnace, and enough fuel to smelt it \
+/// furnaces are only ever opened on purpose like this, place_block never clicks on one
+pub async fn load_furnace(
+	bot: &Client,
+	furnace: BlockPos,
+	input: Item,
+	count: i32,
+) -> anyhow::Result<()> {
+	pathfind::reach(bot, furnace).await?;
+	let container = bot.open_container_at(furnace).await.ok_or_else(|| {
+		anyhow!(
+			"{} couldn't open the furnace at {furnace:?}",
+			bot.username()
+		)
+	})?;
+	let menu = container
+		.menu()
+		.ok_or_else(|| anyhow!("{}'s furnace closed", bot.username()))?;
+	let stacks = super::storage::player_stacks(&menu);
+
+	let contents = container.contents().unwrap_or_default();
+	let slot = |i: usize| contents.get(i).filter(|stack| stack.is_present());
+	if let Some(stack) = slot(INPUT).filter(|stack| stack.kind() != input) {
+		return Err(anyhow!(
+			"the furnace at {furnace:?} is already smelting {:?}",
+			stack.kind()
+		));
+	}
+	let already = slot(INPUT).map(|stack| stack.count()).unwrap_or(0);
+	// right clicking a different fuel would swap it with what's in there
+	let in_fuel = slot(FUEL).map(|stack| (stack.kind(), stack.count()));
+
+	// shift clicking puts anything smeltable in the input slot, a stack at most
+	let mut moved = 0;
+	for (slot, stack) in stacks.iter() {
+		if moved >= count {
+			break;
+		}
+		if stack.kind() == input {
+			container.click(QuickMoveClick::Left { slot: *slot as u16 });
+			moved += stack.count();
+		}
+	}
+	if moved == 0 {
+		return Err(anyhow!("{} doesn't have any {input:?}", bot.username()));
+	}
+	bot.wait_one_update().await;
+
+	// the input slot fills up before the stack runs out
+	let loaded = container
+		.contents()
+		.and_then(|contents| contents.get(INPUT).cloned())
+		.filter(|stack| stack.kind() == input)
+		.map(|stack| stack.count())
+		.unwrap_or(0);
+	if loaded <= already {
+		return Err(anyhow!(
+			"{} couldn't fit any {input:?} in the furnace at {furnace:?}",
+			bot.username()
+		));
+	}
+
+	let burns = in_fuel
+		.and_then(|(kind, count)| Some(fuel_value(kind)? * count as f32))
+		.unwrap_or(0.0);
+	if burns >= loaded as f32 {
+		return Ok(());
+	}
+
+	let Some((fuel_slot, fuel_count, value)) = stacks
+		.iter()
+		.filter(|(_, stack)| in_fuel.is_none_or(|(kind, _)| kind == stack.kind()))
+		.filter_map(|(slot, stack)| Some((*slot, stack.count(), fuel_value(stack.kind())?)))
+		.max_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+	else {
+		return Err(anyhow!(
+			"{} doesn't have any fuel that goes with what's in the furnace at {furnace:?}",
+			bot.username()
+		));
+	};
+
+	// pick up the fuel, drop in as much as it takes and put the rest back
+	let needed = (((loaded as f32 - burns) / value).ceil() as i32).min(fuel_count);
+	container.click(PickupClick::Left {
+		slot: Some(fuel_slot as u16),
+	});
+	for _ in 0..needed {
+		container.click(PickupClick::Right {
+			slot: Some(FUEL as u16),
+		});
+	}
+	container.click(PickupClick::Left {
+		slot: Some(fuel_slot as u16),
+	});
+	bot.wait_one_update().await;
+	Ok(())
+}
+
+/// waits for the furnace to finish if it's almost done and takes what it made
+pub async fn collect_furnace(bot: &Client, furnace: BlockPos) -> anyhow::Result<()> {
+	pathfind::reach(bot, furnace).await?;
+	let container = bot.open_container_at(furnace).await.ok_or_else(|| {
+		anyhow!(
+			"{} couldn't open the furnace at {furnace:?}",
+			bot.username()
+		)
+	})?;
+
+	let smelting = || {
+		container
+			.contents()
+			.and_then(|contents| contents.get(INPUT).map(|stack| stack.is_present()))
+			.unwrap_or(false)
+	};
+	for _ in 0..30 {
+		if !smelting() {
+			break;
+		}
+		pathfind::wait_ticks(bot, 20).await;
+	}
+
+	container.click(QuickMoveClick::Left {
+		slot: OUTPUT as u16,
+	});
+	bot.wait_one_update().await;
+
+	if smelting() {
+		return Err(anyhow!(
+			"the furnace at {furnace:?} is still smelting, {} will come back",
+			bot.username()
+		));
+	}
+	Ok(())
+}
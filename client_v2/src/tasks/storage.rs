@@ -0,0 +1,162 @@
+use std::sync::LazyLock;
+
+use anyhow::anyhow;
+use azalea::{
+	BlockPos, BotClientExt, Client,
+	container::ContainerHandle,
+	inventory::{ItemStack, Menu, operations::QuickMoveClick},
+	prelude::ContainerClientExt,
+	registry::Item,
+};
+
+/// what was in the containers the bots in this process opened, waiting to be reported
+static OPENED: LazyLock<parking_lot::Mutex<Vec<(BlockPos, Vec<(Item, i32)>)>>> =
+	LazyLock::new(Default::default);
+
+/// every container opened since the last call, and what was in it when it was closed
+pub fn take_opened() -> Vec<(BlockPos, Vec<(Item, i32)>)> {
+	std::mem::take(&mut *OPENED.lock())
+}
+
+/// empties the inventory into the chests, except for what's on the keep list \
+/// then takes what it's missing back out of them, closest chest first
+pub async fn stash(
+	bot: &Client,
+	chests: &[BlockPos],
+	keep: &[Item],
+	take: &[(Item, i32)],
+) -> anyhow::Result<()> {
+	let me = bot.position();
+	let mut chests = chests.to_vec();
+	chests.sort_by(|a, b| {
+		a.center()
+			.distance_to(&me)
+			.total_cmp(&b.center().distance_to(&me))
+	});
+
+	// how many more of each it still wants
+	let mut take = take.to_vec();
+	for chest in chests {
+		let left = |menu: &Menu| {
+			player_stacks(menu)
+				.into_iter()
+				.filter(|(_, stack)| !keep.contains(&stack.kind()))
+				.collect::<Vec<_>>()
+		};
+
+		pathfind::reach(bot, chest).await?;
+		let Some(container) = bot.open_container_at(chest).await else {
+			continue;
+		};
+		let Some(menu) = container.menu() else {
+			continue;
+		};
+
+		// shift clicking moves the stack to the first chest slot with room
+		for (slot, _) in left(&menu) {
+			container.click(QuickMoveClick::Left { slot: slot as u16 });
+		}
+		bot.wait_one_update().await;
+
+		for (item, want) in take.iter_mut() {
+			*want -= take_from(bot, &container, *item, *want).await;
+		}
+		take.retain(|(_, want)| *want > 0);
+
+		opened(chest, &container);
+		let full = container.menu().map(|menu| !left(&menu).is_empty());
+		if full == Some(false) && take.is_empty() {
+			return Ok(());
+		}
+	}
+
+	Err(anyhow!(
+		"{} couldn't stash everything, the chests are full or don't have what it needs",
+		bot.username()
+	))
+}
+
+/// takes about `count` of the item out of the container, whole stacks at a time
+pub async fn fetch(
+	bot: &Client,
+	container: BlockPos,
+	item: Item,
+	count: i32,
+) -> anyhow::Result<()> {
+	pathfind::reach(bot, container).await?;
+	let handle = bot.open_container_at(container).await.ok_or_else(|| {
+		anyhow!(
+			"{} couldn't open the container at {container:?}",
+			bot.username()
+		)
+	})?;
+
+	let took = take_from(bot, &handle, item, count).await;
+	opened(container, &handle);
+
+	if took == 0 {
+		return Err(anyhow!(
+			"{} couldn't take any {item:?} out of the container at {container:?}",
+			bot.username()
+		));
+	}
+	Ok(())
+}
+
+/// shift clicks stacks of the item out of the container until it has `want` \
+/// returns how many actually ended up in the inventory, which is less if it ran out of room
+async fn take_from(bot: &Client, container: &ContainerHandle, item: Item, want: i32) -> i32 {
+	let held = || {
+		container
+			.menu()
+			.map(|menu| {
+				player_stacks(&menu)
+					.into_iter()
+					.filter(|(_, stack)| stack.kind() == item)
+					.map(|(_, stack)| stack.count())
+					.sum::<i32>()
+			})
+			.unwrap_or_default()
+	};
+	let before = held();
+
+	let mut clicked = 0;
+	let contents = container.contents().unwrap_or_default();
+	for (slot, stack) in contents.iter().enumerate() {
+		if clicked >= want {
+			break;
+		}
+		if stack.kind() == item {
+			container.click(QuickMoveClick::Left { slot: slot as u16 });
+			clicked += stack.count();
+		}
+	}
+	bot.wait_one_update().await;
+
+	(held() - before).max(0)
+}
+
+/// remembers what's in the container so the coordinator's index can be updated
+fn opened(pos: BlockPos, container: &ContainerHandle) {
+	let mut items = Vec::<(Item, i32)>::new();
+	for stack in container.contents().unwrap_or_default() {
+		if !stack.is_present() {
+			continue;
+		}
+		match items.iter_mut().find(|(item, _)| *item == stack.kind()) {
+			Some((_, count)) => *count += stack.count(),
+			None => items.push((stack.kind(), stack.count())),
+		}
+	}
+	OPENED.lock().push((pos, items));
+}
+
+/// the stacks in the player's part of an open menu, with their slots in the menu
+pub fn player_stacks(menu: &Menu) -> Vec<(usize, ItemStack)> {
+	let range = menu.player_slots_range();
+	menu.slots()
+		.into_iter()
+		.enumerate()
+		.filter(|(slot, stack)| range.contains(slot) && stack.is_present())
+		.collect()
+}
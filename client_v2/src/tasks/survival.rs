@@ -0,0 +1,228 @@
+use std::{
+	collections::HashSet,
+	time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use azalea::{
+	Client, Vec3, pathfinder::goals::RadiusGoal, prelude::PathfinderClientExt, registry::Item,
+};
+use bevy_ecs::entity::Entity;
+
+/// things worth eating, best first
+pub const FOODS: &[Item] = &[
+	Item::GoldenCarrot,
+	Item::CookedBeef,
+	Item::CookedPorkchop,
+	Item::CookedMutton,
+	Item::CookedSalmon,
+	Item::CookedChicken,
+	Item::CookedCod,
+	Item::CookedRabbit,
+	Item::BakedPotato,
+	Item::Bread,
+	Item::Carrot,
+	Item::Apple,
+	Item::MelonSlice,
+	Item::SweetBerries,
+	Item::Potato,
+];
+
+/// how long after getting hurt the bot still runs from whatever did it
+const THREAT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// health and hunger, reported to the coordinator whenever they change
+#[derive(Copy, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Vitals {
+	pub health: f32,
+	pub food: u32,
+}
+impl Default for Vitals {
+	fn default() -> Self {
+		Self {
+			health: 20.0,
+			food: 20,
+		}
+	}
+}
+impl Vitals {
+	pub fn of(bot: &Client) -> Self {
+		Self {
+			health: bot.health(),
+			food: bot.hunger().food,
+		}
+	}
+
+	/// can't sprint anymore, worth dropping whatever it's doing to eat
+	pub fn starving(&self) -> bool {
+		self.food <= 6
+	}
+	/// one or two more hits and it's dead
+	pub fn in_danger(&self) -> bool {
+		self.health <= 6.0
+	}
+	/// worth eating in between tasks \
+	/// health only comes back on its own with 18 food or more
+	pub fn hungry(&self) -> bool {
+		self.food <= 14 || (self.health < 20.0 && self.food < 18)
+	}
+	/// the coordinator doesn't send it to fight below this
+	pub fn can_fight(&self) -> bool {
+		self.health > 8.0
+	}
+}
+
+pub fn has_food(bot: &Client) -> bool {
+	FOODS
+		.iter()
+		.any(|food| pathfind::count_item(bot, *food) > 0)
+}
+
+/// eats the best food it has, returns false if it had nothing to eat
+pub async fn eat(bot: &Client) -> bool {
+	let Some(food) = FOODS
+		.iter()
+		.find(|food| pathfind::count_item(bot, **food) > 0)
+	else {
+		return false;
+	};
+	if !pathfind::hold_item(bot, *food).await {
+		return false;
+	}
+
+	// eating takes 32 ticks of holding right click
+	bot.start_use_item();
+	pathfind::wait_ticks(bot, 40).await;
+	true
+}
+
+/// runs 10 blocks away from `from`, or as far as it gets in 3 seconds
+pub async fn flee(bot: &Client, from: Vec3) {
+	let me = bot.position();
+	let (dx, dz) = (me.x - from.x, me.z - from.z);
+	let distance = (dx * dx + dz * dz).sqrt().max(0.01);
+	let goal = RadiusGoal {
+		pos: Vec3::new(
+			me.x + dx / distance * 10.0,
+			me.y,
+			me.z + dz / distance * 10.0,
+		),
+		radius: 2.0,
+	};
+
+	bot.start_goto(goal);
+	for _ in 0..60 {
+		if bot.is_goto_target_reached() {
+			break;
+		}
+		pathfind::wait_ticks(bot, 1).await;
+	}
+	bot.stop_pathfinding();
+}
+
+/// where whatever hurt the bot was, if it was recent enough to still be a threat
+pub fn threat(hurt_at: Option<(Vec3, Instant)>) -> Option<Vec3> {
+	hurt_at
+		.filter(|(_, at)| at.elapsed() < THREAT_TIMEOUT)
+		.map(|(pos, _)| pos)
+}
+
+/// resolves once the bot has to drop what it's doing and look after itself \
+/// returns why
+pub async fn emergency(bot: &Client, threat: impl Fn() -> Option<Vec3>) -> String {
+	loop {
+		pathfind::wait_ticks(bot, 1).await;
+		let vitals = Vitals::of(bot);
+		if vitals.in_danger() && threat().is_some() {
+			return format!("{} ran away at {} health", bot.username(), vitals.health);
+		}
+		// no point stopping if there's nothing to eat
+		if vitals.starving() && has_food(bot) {
+			return format!("{} stopped to eat at {} food", bot.username(), vitals.food);
+		}
+	}
+}
+
+/// runs away if it's about to die and eats if it's hungry, done in between tasks
+pub async fn recover(bot: &Client, threat: Option<Vec3>) {
+	let vitals = Vitals::of(bot);
+	if vitals.in_danger() {
+		if let Some(threat) = threat {
+			flee(bot, threat).await;
+		}
+	}
+	if vitals.hungry() {
+		eat(bot).await;
+	}
+}
+
+/// items dropped further than this from where the bot died aren't its
+const PICK_UP_RADIUS: f64 = 8.0;
+
+/// walks into every item on the ground near `around`, closest first \
+/// items it can't get to within a few seconds are left where they are
+pub async fn pick_up(bot: &Client, around: Vec3) -> anyhow::Result<()> {
+	bot.goto(RadiusGoal {
+		pos: around,
+		radius: 2.0,
+	})
+	.await;
+
+	let mut given_up = HashSet::new();
+	for _ in 0..40 {
+		let me = bot.position();
+		let Some((entity, item)) = items_near(bot, around, PICK_UP_RADIUS)
+			.into_iter()
+			.filter(|(entity, _)| !given_up.contains(entity))
+			.min_by(|(_, a), (_, b)| a.distance_to(&me).total_cmp(&b.distance_to(&me)))
+		else {
+			break;
+		};
+		let start = Instant::now();
+		bot.start_goto(RadiusGoal {
+			pos: item,
+			radius: 0.5,
+		});
+		while !bot.is_goto_target_reached() && start.elapsed() < Duration::from_secs(5) {
+			pathfind::wait_ticks(bot, 1).await;
+		}
+		bot.stop_pathfinding();
+		pathfind::wait_ticks(bot, 2).await;
+
+		if items_near(bot, around, PICK_UP_RADIUS)
+			.iter()
+			.any(|(other, _)| *other == entity)
+		{
+			given_up.insert(entity);
+		}
+	}
+
+	if given_up.is_empty() {
+		Ok(())
+	} else {
+		Err(anyhow!(
+			"{} couldn't get to {} of the items",
+			bot.username(),
+			given_up.len()
+		))
+	}
+}
+
+/// the items lying on the ground near `around`, and where they are
+pub fn items_near(bot: &Client, around: Vec3, radius: f64) -> Vec<(Entity, Vec3)> {
+	use azalea::{
+		entity::{Dead, Position, metadata::Item as ItemEntity},
+		world::InstanceName,
+	};
+	use bevy_ecs::prelude::{With, Without};
+
+	let instance = bot.component::<InstanceName>();
+	let mut ecs = bot.ecs.lock();
+	let mut query = ecs
+		.query_filtered::<(Entity, &Position, &InstanceName), (With<ItemEntity>, Without<Dead>)>();
+	query
+		.iter(&ecs)
+		.filter(|(_, pos, world)| **world == instance && pos.distance_to(&around) <= radius)
+		.map(|(entity, pos, _)| (entity, **pos))
+		.collect()
+}
@@ -1,12 +1,9 @@
-use std::{ops::Deref, time::Duration};
+use std::time::Duration;
 
-use anyhow::anyhow;
 use azalea::{
-	BlockPos, BotClientExt, Client,
-	entity::{EyeHeight, Position},
+	BlockPos, BotClientExt, Client, Vec3,
 	pathfinder::goals::{Goal, RadiusGoal},
 	prelude::PathfinderClientExt,
-	world::MinecraftEntityId,
 };
 use uuid::Uuid;
 
@@ -17,77 +14,92 @@ pub enum Task {
 	Jump,
 	Goto(RadiusGoal),
 	Mine(BlockPos),
+	/// places the block at pos, using the matching item from the inventory
+	Place {
+		pos: BlockPos,
+		block: azalea::registry::Block,
+	},
 	Attack(Uuid),
+	/// mines something nearby that drops the item and picks it up
+	Gather(azalea::registry::Item),
+	/// fells a whole tree and plants saplings where it stood
+	Fell {
+		logs: Vec<BlockPos>,
+		replant: Vec<(BlockPos, azalea::registry::Block)>,
+	},
+	/// harvests a fully grown crop and replants it
+	Harvest {
+		pos: BlockPos,
+		crop: azalea::registry::Block,
+	},
+	/// places any scaffolding block there, to plug up lava or water
+	Plug(BlockPos),
+	/// pillars up at pos until its feet are at to_y
+	Pillar {
+		pos: BlockPos,
+		to_y: i32,
+	},
+	/// builds a walkway from `from` to `to`, at the height of `from`
+	Bridge {
+		from: BlockPos,
+		to: BlockPos,
+	},
+	/// puts everything not on the keep list into the chests and takes what's on the take list out of them
+	Stash {
+		chests: Vec<BlockPos>,
+		keep: Vec<azalea::registry::Item>,
+		take: Vec<(azalea::registry::Item, i32)>,
+	},
+	/// takes about `count` of the item out of the container
+	Fetch {
+		container: BlockPos,
+		item: azalea::registry::Item,
+		count: i32,
+	},
+	/// crafts at least `count` of the item, and whatever goes into it if it has to
+	Craft {
+		item: azalea::registry::Item,
+		count: i32,
+	},
+	/// puts about `count` of the input and enough fuel for it in the furnace
+	LoadFurnace {
+		furnace: BlockPos,
+		input: azalea::registry::Item,
+		count: i32,
+	},
+	/// takes what the furnace made, waiting for it to finish if it's almost done
+	CollectFurnace(BlockPos),
+	/// walks up to a player or bot and tosses them the items
+	Deliver {
+		to: String,
+		item: azalea::registry::Item,
+		count: i32,
+	},
+	/// picks up the items lying around where a bot died
+	PickUp(Vec3),
+	/// says something in chat, it's how the server answers commands
+	Say(String),
 }
 impl Task {
+	/// the block this task is working on, if any
+	pub fn pos(&self) -> Option<BlockPos> {
+		match self {
+			Self::Mine(pos) => Some(*pos),
+			Self::Place { pos, .. } => Some(*pos),
+			Self::Harvest { pos, .. } => Some(*pos),
+			Self::Plug(pos) => Some(*pos),
+			Self::CollectFurnace(pos) => Some(*pos),
+			Self::Pillar { pos, .. } => Some(*pos),
+			Self::Bridge { from, .. } => Some(*from),
+			Self::Fell { logs, .. } => logs.iter().min_by_key(|pos| pos.y).copied(),
+			_ => None,
+		}
+	}
+
 	pub async fn execute(&self, bot: &Client) -> anyhow::Result<()> {
 		match self {
 			Self::Attack(uuid) => {
-				let entity = bot
-					.entity_by_uuid(*uuid)
-					.ok_or_else(|| anyhow!("couldn't find an entity with uuid {uuid}"))?;
-
-				let eid: MinecraftEntityId = bot.get_entity_component(entity).ok_or_else(|| {
-					anyhow!(
-						"there wasn't an entityid component on the entity {} was supposed to attack",
-						bot.username()
-					)
-				})?;
-
-				let eye_offset: Option<EyeHeight> = bot.get_entity_component(entity);
-				let eye_offset = eye_offset.map(|a| a.deref().clone()).unwrap_or_default();
-
-				let pos_now = || {
-					let pos: Position = bot.get_entity_component(entity).ok_or_else(|| {
-						anyhow!(
-							"there wasn't a position component on the entity {} was supposed to attack",
-							bot.username()
-						)
-					})?;
-					let pos = pos.down(0.0);
-					anyhow::Ok(pos)
-				};
-
-				let pos = loop {
-					let start_pos = pos_now()?;
-					let goal = RadiusGoal {
-						pos: start_pos,
-						radius: 3.5,
-					};
-					if goal.success(bot.position().to_block_pos_floor()) {
-						break start_pos;
-					} else {
-						// this is a reimplementation of bot.goto that will change the target if it moved too far
-						bot.start_goto(goal);
-						bot.wait_one_update().await;
-
-						let mut tick_broadcaster = bot.get_tick_broadcaster();
-						'pathing: while !bot.is_goto_target_reached() {
-							// check every tick
-							match tick_broadcaster.recv().await {
-								Ok(_) => (),
-								Err(_err) => (),
-							};
-							let pos = pos_now()?;
-							if pos.distance_to(&start_pos) >= 5.0 {
-								bot.stop_pathfinding();
-								println!(
-									"looping again in Attack, since the target entity has moved at least 5 blocks"
-								);
-								tokio::time::sleep(Duration::from_millis(100)).await;
-								break 'pathing;
-							}
-						}
-					}
-				};
-
-				bot.look_at(pos.up(eye_offset as _));
-				bot.jump();
-				tokio::time::sleep(Duration::from_millis(400)).await;
-
-				bot.look_at(pos.up(eye_offset as _));
-				bot.attack(eid);
-				tokio::time::sleep(Duration::from_millis(400)).await
+				super::combat::attack(bot, *uuid).await?;
 			}
 			Self::Jump => {
 				bot.jump();
@@ -106,20 +118,66 @@ impl Task {
 					.map(|state| state.is_air())
 					.unwrap_or(false)
 				{
-					let goal = RadiusGoal {
-						pos: pos.center(),
-						radius: 3.5,
-					};
-
-					if !goal.success(bot.position().to_block_pos_floor()) {
-						bot.goto(goal).await;
-					}
-					bot.look_at(pos.center());
-					bot.mine(*pos).await;
+					// builds its way there if it has to
+					pathfind::reach(bot, *pos).await?;
+					pathfind::mine_with_best_tool(bot, *pos).await?;
 
 					tokio::time::sleep(Duration::from_millis(50)).await;
 				}
 			}
+			Self::Place { pos, block } => {
+				pathfind::place_block(bot, *pos, *block).await?;
+			}
+			Self::Gather(item) => {
+				super::gather::gather(bot, *item).await?;
+			}
+			Self::Fell { logs, replant } => {
+				super::lumber::fell(bot, logs, replant).await?;
+			}
+			Self::Harvest { pos, crop } => {
+				super::farm::harvest(bot, *pos, *crop).await?;
+			}
+			Self::Plug(pos) => {
+				pathfind::place_scaffolding(bot, *pos).await?;
+			}
+			Self::Pillar { pos, to_y } => {
+				pathfind::pillar(bot, *pos, *to_y).await?;
+			}
+			Self::Bridge { from, to } => {
+				pathfind::bridge(bot, *from, *to).await?;
+			}
+			Self::Stash { chests, keep, take } => {
+				super::storage::stash(bot, chests, keep, take).await?;
+			}
+			Self::Fetch {
+				container,
+				item,
+				count,
+			} => {
+				super::storage::fetch(bot, *container, *item, *count).await?;
+			}
+			Self::Craft { item, count } => {
+				super::craft::craft(bot, *item, *count).await?;
+			}
+			Self::LoadFurnace {
+				furnace,
+				input,
+				count,
+			} => {
+				super::smelt::load_furnace(bot, *furnace, *input, *count).await?;
+			}
+			Self::CollectFurnace(furnace) => {
+				super::smelt::collect_furnace(bot, *furnace).await?;
+			}
+			Self::Deliver { to, item, count } => {
+				super::deliver::deliver(bot, to, *item, *count).await?;
+			}
+			Self::PickUp(around) => {
+				super::survival::pick_up(bot, *around).await?;
+			}
+			Self::Say(message) => {
+				bot.chat(message);
+			}
 			Self::Halt => {}
 		}
 		Ok(())
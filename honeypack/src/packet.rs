@@ -30,7 +30,7 @@ impl<T: DeserializeOwned> Packet<T> {
 
 		let len = read.read_u32().await?;
 		let mut buf = vec![0_u8; len as usize];
-		read.read(&mut buf).await?;
+		read.read_exact(&mut buf).await?;
 
 		let data: T = bincode::deserialize(&buf)?;
 
@@ -45,7 +45,7 @@ impl<T: Serialize> Packet<T> {
 		let len = buf.len() as u32;
 
 		write.write_u32(len).await?;
-		write.write(&buf).await?;
+		write.write_all(&buf).await?;
 
 		Ok(())
 	}
@@ -0,0 +1,66 @@
+use azalea::{
+	BotClientExt, Client,
+	inventory::{ItemStack, operations::SwapClick},
+	prelude::ContainerClientExt,
+	registry::Item,
+};
+
+/// the player inventory menu's hotbar, see azalea::inventory::Player
+pub(crate) const HOTBAR: std::ops::RangeInclusive<usize> = 36..=44;
+/// the hotbar slot items from the rest of the inventory get swapped into
+const SWAP_SLOT: u8 = 8;
+
+/// every slot of the player's inventory menu with the item in it
+pub fn inventory_slots(bot: &Client) -> Vec<(usize, ItemStack)> {
+	bot.menu().slots().into_iter().enumerate().collect()
+}
+
+/// how many of the item the bot has in total
+pub fn count_item(bot: &Client, item: Item) -> i32 {
+	inventory_slots(bot)
+		.into_iter()
+		.filter(|(_, stack)| stack.kind() == item)
+		.map(|(_, stack)| stack.count())
+		.sum()
+}
+
+/// puts the item in the bot's hand, moving it to the hotbar first if it has to \
+/// returns false if the bot doesn't have the item at all
+pub async fn hold_item(bot: &Client, item: Item) -> bool {
+	hold_slot_matching(bot, |stack| stack.kind() == item).await
+}
+
+/// like hold_item but for the first stack `f` returns true for
+pub async fn hold_slot_matching(bot: &Client, f: impl Fn(&ItemStack) -> bool) -> bool {
+	let slots = inventory_slots(bot);
+	let Some((slot, _)) = slots
+		.iter()
+		.filter(|(_, stack)| stack.is_present() && f(stack))
+		// prefer what's already in the hotbar
+		.min_by_key(|(slot, _)| !HOTBAR.contains(slot))
+	else {
+		return false;
+	};
+	hold_slot(bot, *slot).await
+}
+
+/// puts the stack in the given inventory menu slot in the bot's hand
+pub async fn hold_slot(bot: &Client, slot: usize) -> bool {
+	let hotbar_slot = if HOTBAR.contains(&slot) {
+		(slot - HOTBAR.start()) as u8
+	} else {
+		let Some(inventory) = bot.open_inventory() else {
+			// there's a container open, the inventory can't be touched
+			return false;
+		};
+		inventory.click(SwapClick {
+			source_slot: slot as u16,
+			target_slot: SWAP_SLOT,
+		});
+		bot.wait_one_update().await;
+		SWAP_SLOT
+	};
+
+	bot.set_selected_hotbar_slot(hotbar_slot);
+	true
+}
@@ -0,0 +1,221 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use azalea::{
+	BlockPos, BotClientExt, Client, Vec3,
+	blocks::BlockState,
+	core::direction::Direction,
+	pathfinder::goals::{BlockPosGoal, InverseGoal},
+	prelude::PathfinderClientExt,
+	registry::{Block, Item, tags},
+};
+
+use crate::hold_item;
+
+const DIRECTIONS: [Direction; 6] = [
+	Direction::Down,
+	Direction::Up,
+	Direction::North,
+	Direction::South,
+	Direction::West,
+	Direction::East,
+];
+
+pub async fn place_block(bot: &Client, pos: BlockPos, block: Block) -> anyhow::Result<()> {
+	// goal: place the block at pos by right clicking a face of one of the blocks next to it
+	// non-goal (for now): placing support blocks if there's nothing to click on
+
+	for _ in 0..3 {
+		match bot.world().read().get_block_state(&pos) {
+			Some(state) if placed_as(state, block) => return Ok(()),
+			Some(state) if !replaceable(state) => {
+				return Err(anyhow!(
+					"there's already a {:?} at {pos:?}",
+					Block::from(state)
+				));
+			}
+			_ => {}
+		}
+
+		crate::reach(bot, pos).await?;
+		// can't place a block where we're standing
+		let feet = bot.position().to_block_pos_floor();
+		if feet == pos || feet.up(1) == pos {
+			bot.goto(InverseGoal(BlockPosGoal(feet))).await;
+		}
+
+		let item = item_for(block)?;
+		if !hold_item(bot, item).await {
+			return Err(anyhow!("{} doesn't have any {item:?}", bot.username()));
+		}
+
+		let (right_click_block, look_at) = nearest_block(bot, pos)
+			.ok_or_else(|| anyhow!("there's nothing to place {block:?} against at {pos:?}"))?;
+		bot.look_at(look_at);
+		// the face we're clicking comes from what we're looking at, which is only updated on the next tick
+		wait_ticks(bot, 1).await;
+		// we can't sneak, so clicking a furnace or a door would open it instead of placing against it \
+		// nearest_block just doesn't return anything that could be opened
+		bot.block_interact(right_click_block);
+
+		wait_ticks(bot, 4).await;
+	}
+
+	match bot.world().read().get_block_state(&pos) {
+		Some(state) if placed_as(state, block) => Ok(()),
+		_ => Err(anyhow!(
+			"placed {block:?} at {pos:?} but it never showed up"
+		)),
+	}
+}
+
+/// returns (block we can right click, where to look when clicking)
+fn nearest_block(bot: &Client, pos: BlockPos) -> Option<(BlockPos, Vec3)> {
+	let eyes = bot.eye_position();
+
+	let world = bot.world();
+	let world = world.read();
+	DIRECTIONS
+		.iter()
+		.map(|dir| pos.offset_with_direction(*dir))
+		.filter(|test| {
+			world
+				.get_block_state(test)
+				.map(|state| !replaceable(state) && !interactable(state))
+				.unwrap_or(false)
+		})
+		.map(|test| {
+			// the middle of the face that's touching pos
+			let (a, b) = (test.center(), pos.center());
+			let face = Vec3::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0, (a.z + b.z) / 2.0);
+			(test, face)
+		})
+		.min_by(|(_, a), (_, b)| a.distance_to(&eyes).total_cmp(&b.distance_to(&eyes)))
+}
+
+/// air, fluids and plants that just get replaced when a block is placed into them
+pub fn replaceable(state: BlockState) -> bool {
+	if state.is_air() {
+		return true;
+	}
+	matches!(
+		Block::from(state),
+		Block::Water
+			| Block::Lava
+			| Block::ShortGrass
+			| Block::TallGrass
+			| Block::Fern
+			| Block::LargeFern
+			| Block::DeadBush
+			| Block::Vine
+			| Block::Snow
+			| Block::Seagrass
+			| Block::TallSeagrass
+			| Block::Fire
+	)
+}
+
+/// blocks that do something when they're right clicked, instead of letting you place against them
+pub fn interactable(state: BlockState) -> bool {
+	let block = Block::from(state);
+	let tagged = [
+		&tags::blocks::BEDS,
+		&tags::blocks::DOORS,
+		&tags::blocks::TRAPDOORS,
+		&tags::blocks::FENCE_GATES,
+		&tags::blocks::BUTTONS,
+		&tags::blocks::SHULKER_BOXES,
+		&tags::blocks::ANVIL,
+		&tags::blocks::ALL_SIGNS,
+		&tags::blocks::FLOWER_POTS,
+		&tags::blocks::CAMPFIRES,
+		&tags::blocks::CANDLE_CAKES,
+	];
+	tagged.iter().any(|tag| tag.contains(&block))
+		|| matches!(
+			block,
+			Block::Chest
+				| Block::TrappedChest
+				| Block::EnderChest
+				| Block::Barrel
+				| Block::Furnace
+				| Block::BlastFurnace
+				| Block::Smoker
+				| Block::Hopper
+				| Block::Dispenser
+				| Block::Dropper
+				| Block::Crafter
+				| Block::CraftingTable
+				| Block::CartographyTable
+				| Block::FletchingTable
+				| Block::SmithingTable
+				| Block::EnchantingTable
+				| Block::Lever
+				| Block::BrewingStand
+				| Block::Beacon
+				| Block::Loom
+				| Block::Grindstone
+				| Block::Stonecutter
+				| Block::Lectern
+				| Block::NoteBlock
+				| Block::Jukebox
+				| Block::Repeater
+				| Block::Comparator
+				| Block::DaylightDetector
+				| Block::Cake
+				| Block::Bell
+				| Block::Composter
+				| Block::RespawnAnchor
+				| Block::ChiseledBookshelf
+				| Block::DecoratedPot
+				| Block::CommandBlock
+				| Block::ChainCommandBlock
+				| Block::RepeatingCommandBlock
+				| Block::StructureBlock
+				| Block::Jigsaw
+		)
+}
+
+/// whether the state is what placing the block ended up as \
+/// torches turn into wall torches and such depending on what they were placed against
+pub fn placed_as(state: BlockState, block: Block) -> bool {
+	let placed = Block::from(state);
+	placed == block || placed.to_string().replace("wall_", "") == block.to_string()
+}
+
+/// places whatever scaffolding the bot has at pos
+pub async fn place_scaffolding(bot: &Client, pos: BlockPos) -> anyhow::Result<()> {
+	let block = crate::SCAFFOLDING
+		.iter()
+		.find(|item| crate::count_item(bot, **item) > 0)
+		.and_then(|item| Block::from_str(&item.to_string()).ok())
+		.ok_or_else(|| anyhow!("{} doesn't have any scaffolding", bot.username()))?;
+	// place_block -> reach -> bridge_toward -> place_scaffolding is recursive, so it has to be boxed
+	Box::pin(place_block(bot, pos, block)).await
+}
+
+/// the item that places the block
+pub fn item_for(block: Block) -> anyhow::Result<Item> {
+	// crops are planted with seeds
+	match block {
+		Block::Wheat => return Ok(Item::WheatSeeds),
+		Block::Carrots => return Ok(Item::Carrot),
+		Block::Potatoes => return Ok(Item::Potato),
+		Block::Beetroots => return Ok(Item::BeetrootSeeds),
+		_ => {}
+	}
+	let id = block.to_string();
+	// wall torches, wall signs and such are placed with the regular item
+	let id = id.replace("wall_", "");
+	Item::from_str(&id).map_err(|_| anyhow!("there's no item that places {block:?}"))
+}
+
+pub async fn wait_ticks(bot: &Client, ticks: usize) {
+	let mut tick_broadcaster = bot.get_tick_broadcaster();
+	for _ in 0..ticks {
+		match tick_broadcaster.recv().await {
+			Ok(_) => (),
+			Err(_err) => (),
+		};
+	}
+}
@@ -0,0 +1,176 @@
+use anyhow::anyhow;
+use azalea::{
+	BlockPos, BotClientExt, Client, Vec3,
+	pathfinder::goals::{BlockPosGoal, RadiusGoal},
+	prelude::PathfinderClientExt,
+	registry::Item,
+};
+
+use crate::{clear_block, hold_slot_matching, place_scaffolding, replaceable, wait_ticks};
+
+/// how far away a block can be mined or placed against from
+pub const REACH: f64 = 4.5;
+/// how many blocks a single bridge or pillar can be
+const MAX_SCAFFOLDING: usize = 64;
+
+/// cheap blocks it's fine to build with and leave behind
+pub const SCAFFOLDING: &[Item] = &[
+	Item::Dirt,
+	Item::Cobblestone,
+	Item::CobbledDeepslate,
+	Item::Netherrack,
+	Item::Andesite,
+	Item::Diorite,
+	Item::Granite,
+	Item::Tuff,
+];
+
+/// jumps and places a block under itself, ending up one block higher
+pub async fn pillar_up(bot: &Client) -> anyhow::Result<()> {
+	bot.stop_pathfinding();
+	if !hold_slot_matching(bot, |stack| SCAFFOLDING.contains(&stack.kind())).await {
+		return Err(anyhow!("{} has nothing to pillar up with", bot.username()));
+	}
+
+	let feet = bot.position().to_block_pos_floor();
+	let below = feet.down(1);
+	// straight down at the top face of the block we're standing on
+	bot.look_at(Vec3::new(
+		feet.x as f64 + 0.5,
+		feet.y as f64,
+		feet.z as f64 + 0.5,
+	));
+	wait_ticks(bot, 1).await;
+	bot.jump();
+
+	// we have to be a whole block up before the block fits under us
+	for _ in 0..10 {
+		wait_ticks(bot, 1).await;
+		if bot.position().y >= feet.y as f64 + 1.0 {
+			break;
+		}
+	}
+	bot.block_interact(below);
+	wait_ticks(bot, 6).await;
+
+	if bot.position().to_block_pos_floor().y > feet.y {
+		Ok(())
+	} else {
+		Err(anyhow!("{} couldn't pillar up at {feet:?}", bot.username()))
+	}
+}
+
+/// mines whatever it's standing on until its feet are at `y`
+pub async fn dig_down(bot: &Client, y: i32) -> anyhow::Result<()> {
+	bot.stop_pathfinding();
+	while bot.position().to_block_pos_floor().y > y {
+		let feet = bot.position().to_block_pos_floor();
+		let below = feet.down(1);
+		// it doesn't need what it digs through, so it's fine to do by hand
+		clear_block(bot, below).await;
+		wait_ticks(bot, 8).await;
+
+		if bot.position().to_block_pos_floor().y >= feet.y {
+			return Err(anyhow!("{} couldn't dig down at {feet:?}", bot.username()));
+		}
+	}
+	Ok(())
+}
+
+/// gets within reach of pos, building its way there if the pathfinder can't find a way
+pub async fn reach(bot: &Client, pos: BlockPos) -> anyhow::Result<()> {
+	let in_reach = |bot: &Client| pos.center().distance_to(&bot.eye_position()) <= REACH;
+	if in_reach(bot) {
+		return Ok(());
+	}
+	bot.goto(RadiusGoal {
+		pos: pos.center(),
+		radius: 3.5,
+	})
+	.await;
+	if in_reach(bot) {
+		return Ok(());
+	}
+
+	// the pathfinder got us as close as it could, the rest has to be built
+	let feet = bot.position().to_block_pos_floor();
+	bridge_toward(bot, BlockPos::new(pos.x, feet.y, pos.z), in_reach).await?;
+	let mut pillared = 0;
+	while !in_reach(bot) && bot.position().y < pos.y as f64 && pillared < MAX_SCAFFOLDING {
+		pillar_up(bot).await?;
+		pillared += 1;
+	}
+
+	if in_reach(bot) {
+		Ok(())
+	} else {
+		Err(anyhow!("{} couldn't get to {pos:?}", bot.username()))
+	}
+}
+
+/// walks to `pos` and pillars up until its feet are at `y`
+pub async fn pillar(bot: &Client, pos: BlockPos, y: i32) -> anyhow::Result<()> {
+	bot.goto(BlockPosGoal(pos)).await;
+	for _ in 0..MAX_SCAFFOLDING {
+		if bot.position().to_block_pos_floor().y >= y {
+			return Ok(());
+		}
+		pillar_up(bot).await?;
+	}
+	Err(anyhow!("{} gave up pillaring up to {y}", bot.username()))
+}
+
+/// walks to `from` and builds a walkway from there to `to`, at the height of `from`
+pub async fn bridge(bot: &Client, from: BlockPos, to: BlockPos) -> anyhow::Result<()> {
+	bot.goto(BlockPosGoal(from)).await;
+	bridge_toward(bot, BlockPos::new(to.x, from.y, to.z), |_| false).await
+}
+
+/// walks straight toward `to`, placing scaffolding wherever there's no floor, until `stop` says so
+async fn bridge_toward(
+	bot: &Client,
+	to: BlockPos,
+	stop: impl Fn(&Client) -> bool,
+) -> anyhow::Result<()> {
+	for _ in 0..MAX_SCAFFOLDING {
+		let feet = bot.position().to_block_pos_floor();
+		if stop(bot) || (feet.x == to.x && feet.z == to.z) {
+			return Ok(());
+		}
+
+		// one axis at a time, the longer one first
+		let (dx, dz) = (to.x - feet.x, to.z - feet.z);
+		let next = if dx.abs() >= dz.abs() {
+			BlockPos::new(feet.x + dx.signum(), feet.y, feet.z)
+		} else {
+			BlockPos::new(feet.x, feet.y, feet.z + dz.signum())
+		};
+
+		for pos in [next.up(1), next] {
+			let solid = bot
+				.world()
+				.read()
+				.get_block_state(&pos)
+				.map(|state| !replaceable(state))
+				.unwrap_or(false);
+			if solid {
+				clear_block(bot, pos).await;
+			}
+		}
+		let floor = bot
+			.world()
+			.read()
+			.get_block_state(&next.down(1))
+			.map(replaceable)
+			.unwrap_or(true);
+		if floor {
+			place_scaffolding(bot, next.down(1)).await?;
+		}
+
+		bot.goto(BlockPosGoal(next)).await;
+		if bot.position().to_block_pos_floor() != next {
+			return Err(anyhow!("{} couldn't step onto {next:?}", bot.username()));
+		}
+	}
+	Err(anyhow!("{} gave up bridging to {to:?}", bot.username()))
+}
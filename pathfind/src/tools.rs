@@ -0,0 +1,202 @@
+use azalea::{
+	BlockPos, BotClientExt, Client,
+	blocks::{Block as _, BlockState},
+	inventory::{ItemStack, components::Damage},
+	registry::{Block, Item, tags},
+};
+
+use crate::{HOTBAR, hold_slot, inventory_slots};
+
+/// tools with this many uses left are put away instead of being broken
+const MIN_DURABILITY: i32 = 5;
+
+/// the bot has nothing that can break the block and still get its drop
+#[derive(Debug)]
+pub struct NoTool {
+	pub username: String,
+	pub block: Block,
+}
+impl std::fmt::Display for NoTool {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} has no tool for {:?}", self.username, self.block)
+	}
+}
+impl std::error::Error for NoTool {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ToolKind {
+	Pickaxe,
+	Axe,
+	Shovel,
+	Hoe,
+	Sword,
+}
+
+/// what a tool's made of decides how fast it mines, what it can mine and how long it lasts
+#[derive(Copy, Clone, Debug)]
+struct Tier {
+	/// how many times faster than a hand it mines
+	speed: f32,
+	/// 0 for wood and gold up to 4 for netherite, see `required_level`
+	level: u8,
+	durability: i32,
+}
+
+fn tier(material: &str) -> Option<Tier> {
+	let (speed, level, durability) = match material {
+		"wooden" => (2.0, 0, 59),
+		"stone" => (4.0, 1, 131),
+		"iron" => (6.0, 2, 250),
+		"diamond" => (8.0, 3, 1561),
+		"netherite" => (9.0, 4, 2031),
+		"golden" => (12.0, 0, 32),
+		_ => return None,
+	};
+	Some(Tier {
+		speed,
+		level,
+		durability,
+	})
+}
+
+/// the kind and tier of the item, if it's a tool
+fn tool(item: Item) -> Option<(ToolKind, Tier)> {
+	let id = item.to_string();
+	let id = id.strip_prefix("minecraft:")?;
+	let (material, kind) = id.rsplit_once('_')?;
+	let kind = match kind {
+		"pickaxe" => ToolKind::Pickaxe,
+		"axe" => ToolKind::Axe,
+		"shovel" => ToolKind::Shovel,
+		"hoe" => ToolKind::Hoe,
+		"sword" => ToolKind::Sword,
+		_ => return None,
+	};
+	Some((kind, tier(material)?))
+}
+
+pub fn is_tool(item: Item) -> bool {
+	tool(item).is_some()
+}
+
+/// the kind of tool that mines the block fastest, if there is one
+pub fn tool_kind(block: Block) -> Option<ToolKind> {
+	if tags::blocks::MINEABLE_PICKAXE.contains(&block) {
+		Some(ToolKind::Pickaxe)
+	} else if tags::blocks::MINEABLE_AXE.contains(&block) {
+		Some(ToolKind::Axe)
+	} else if tags::blocks::MINEABLE_SHOVEL.contains(&block) {
+		Some(ToolKind::Shovel)
+	} else if tags::blocks::MINEABLE_HOE.contains(&block) {
+		Some(ToolKind::Hoe)
+	} else {
+		None
+	}
+}
+
+/// the tier a tool has to be for the block to drop anything
+fn required_level(block: Block) -> u8 {
+	if tags::blocks::NEEDS_DIAMOND_TOOL.contains(&block) {
+		3
+	} else if tags::blocks::NEEDS_IRON_TOOL.contains(&block) {
+		2
+	} else if tags::blocks::NEEDS_STONE_TOOL.contains(&block) {
+		1
+	} else {
+		0
+	}
+}
+
+/// how many more uses the stack has before it breaks, None if it's not a tool
+pub fn durability_left(stack: &ItemStack) -> Option<i32> {
+	let (_, tier) = tool(stack.kind())?;
+	let damage = stack
+		.as_present()
+		.and_then(|data| data.components.get::<Damage>())
+		.map(|damage| damage.amount)
+		.unwrap_or_default();
+	Some(tier.durability - damage)
+}
+
+/// worth putting away so it doesn't break
+pub fn about_to_break(stack: &ItemStack) -> bool {
+	durability_left(stack).is_some_and(|left| left <= MIN_DURABILITY)
+}
+
+/// holds the fastest tool for the block that isn't about to break \
+/// holds something that can't break if no tool helps, errors with NoTool if the block needs a tool it doesn't have
+pub async fn hold_best_tool(bot: &Client, state: BlockState) -> anyhow::Result<()> {
+	let block = Block::from(state);
+	let needs_tool = {
+		let behavior: Box<dyn azalea::blocks::Block> = state.into();
+		behavior.behavior().requires_correct_tool_for_drops
+	};
+	let level = required_level(block);
+
+	let best = tool_kind(block).and_then(|kind| {
+		inventory_slots(bot)
+			.into_iter()
+			.filter(|(_, stack)| stack.is_present() && !about_to_break(stack))
+			.filter_map(|(slot, stack)| {
+				let (tool_kind, tier) = tool(stack.kind())?;
+				(tool_kind == kind && tier.level >= level).then_some((slot, tier.speed))
+			})
+			.max_by(|(_, a), (_, b)| a.total_cmp(b))
+	});
+
+	match best {
+		Some((slot, _)) => {
+			hold_slot(bot, slot).await;
+		}
+		None if needs_tool => {
+			return Err(NoTool {
+				username: bot.username(),
+				block,
+			}
+			.into());
+		}
+		None => hold_nothing_breakable(bot).await,
+	}
+	Ok(())
+}
+
+/// mines the block with the best tool for it, see hold_best_tool
+pub async fn mine_with_best_tool(bot: &Client, pos: BlockPos) -> anyhow::Result<()> {
+	let state = bot.world().read().get_block_state(&pos);
+	if let Some(state) = state {
+		hold_best_tool(bot, state).await?;
+	}
+	bot.look_at(pos.center());
+	bot.mine(pos).await;
+	Ok(())
+}
+
+/// mines a block that's only in the way, with the best tool for it or by hand if nothing gets its drop
+pub async fn clear_block(bot: &Client, pos: BlockPos) {
+	let state = bot.world().read().get_block_state(&pos);
+	if let Some(state) = state {
+		if hold_best_tool(bot, state).await.is_err() {
+			hold_nothing_breakable(bot).await;
+		}
+	}
+	bot.look_at(pos.center());
+	bot.mine(pos).await;
+}
+
+/// swaps away from a tool so mining something by hand doesn't use it up
+async fn hold_nothing_breakable(bot: &Client) {
+	let slots = inventory_slots(bot);
+	// an empty hotbar slot is best, anything that isn't a tool is fine too
+	let slot = slots
+		.iter()
+		.filter(|(slot, _)| HOTBAR.contains(slot))
+		.find(|(_, stack)| !stack.is_present())
+		.or_else(|| {
+			slots
+				.iter()
+				.find(|(_, stack)| stack.is_present() && durability_left(stack).is_none())
+		});
+	if let Some((slot, _)) = slot {
+		hold_slot(bot, *slot).await;
+	}
+}